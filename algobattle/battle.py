@@ -0,0 +1,424 @@
+"""Programmatic entry point for running battles without the command line interface."""
+import os
+import logging
+import configparser
+from enum import IntEnum
+
+import algobattle
+from algobattle.match import Match
+from algobattle.team import Team
+from algobattle.problem import Problem, ProblemLoadError
+from algobattle.battle_wrapper import get_battle_types
+from algobattle.util import calculate_points, load_config, ConfigError, read_run_parameter
+from algobattle.checkpoint import Checkpoint
+
+logger = logging.getLogger('algobattle.battle')
+
+
+REQUIRED_RUN_PARAMETERS = ['timeout_build', 'timeout_generator', 'timeout_solver', 'space_generator', 'space_solver',
+                           'iteration_cap', 'aproximation_iterations']
+
+
+class ExitCode(IntEnum):
+    """Exit codes of the battle script, distinguishing the major failure categories for automated pipelines."""
+
+    # The run was completed.
+    SUCCESS = 0
+    # An unexpected error occurred.
+    ERROR = 1
+    # The options, the config file, the problem or the paths of the programs are invalid.
+    CONFIG_ERROR = 2
+    # The docker containers of the programs could not be built.
+    BUILD_ERROR = 3
+    # A program disqualified itself by not producing a valid output, in every fight of a battle.
+    DISQUALIFIED = 4
+
+
+class BuildError(Exception):
+    """Raised if the docker containers of a match could not be built."""
+
+
+class BattleSetupError(ValueError):
+    """Raised if the arguments of a battle are invalid, before any fight is run."""
+
+
+class BattleResult:
+    """BattleResult class, holding the results of all battles of a match.
+
+    Besides the results as returned by match.run(), the records of every
+    single fight are kept, containing the reached instance sizes, the
+    scores, the timings, the seeds and the captured output of the
+    containers of each fight. If failing instances were saved, they are
+    referenced in failing_instances together with the reason of the failure. Passing the seed of
+    the result to run_battle replays the match with the same seeds. The docker
+    images that ran the programs are identified in images by their ids, digests
+    and base images. The wall-clock time spent in each phase of the match is
+    kept in phase_times.
+    """
+
+    def __init__(self, battle_type: str, iterations: int, team_names: list, results: dict, fights: list,
+                 seed: int = None, config: configparser.ConfigParser = None, failing_instances: list = None,
+                 images: dict = None, phase_times: dict = None) -> None:
+        self.battle_type = battle_type
+        self.iterations = iterations
+        self.team_names = team_names
+        self.results = results
+        self.fights = fights
+        self.seed = seed
+        self.config = config
+        self.failing_instances = failing_instances or []
+        self.images = images or dict()
+        self.phase_times = phase_times or dict()
+
+    def disqualified_teams(self) -> list:
+        """Determine the teams whose generator or solver failed every fight in which it was run.
+
+        The program that failed a fight is taken from the failed_role of its record.
+
+        Returns
+        -------
+        list
+            The names of the disqualified teams, in the order of team_names.
+        """
+        disqualified_teams = []
+        for team_name in self.team_names:
+            for role, team_key in [('solver', 'solving_team'), ('generator', 'generating_team')]:
+                team_fights = [fight for fight in self.fights if fight.get(team_key) == team_name]
+                if team_fights and all(fight.get('failed_role') == role for fight in team_fights):
+                    disqualified_teams.append(team_name)
+                    break
+        return disqualified_teams
+
+    def calculate_points(self, achievable_points: int = 100) -> dict:
+        """Calculate the number of points that each team achieved.
+
+        Parameters
+        ----------
+        achievable_points : int
+            Number of achievable points.
+
+        Returns
+        -------
+        dict
+            A mapping between team names and their achieved points.
+        """
+        return calculate_points(self.results, achievable_points, self.team_names, self.iterations, self.battle_type,
+                                config=self.config)
+
+
+def run_battle(problem, generator_path, solver_path, *, config: str = None, team_names: list = None,
+               battle_type: str = 'iterated', iterations: int = 5, approximation_ratio: float = 1.0,
+               approximation_instance_size: int = 10, runtime_overhead: float = 0,
+               cache_docker_containers: bool = True, fight_listeners: list = None, seed: int = None,
+               log_directory: str = None, failing_instances_directory: str = None, jobs: int = 1,
+               checkpoint: str = None, resume: str = None, instance_sizes: list = None) -> BattleResult:
+    """Build the programs of all teams and run a match between them.
+
+    Parameters
+    ----------
+    problem : Problem or str
+        The problem object or the path to the problem directory.
+    generator_path : str or list
+        Path to the generator, or a list of generator paths for multiple teams.
+        A path may be None if the battle type is 'comparison'.
+    solver_path : str or list
+        Path to the solver, or a list of solver paths for multiple teams.
+    config : str
+        Path to a .ini, .toml or .yaml configuration file. Defaults to the packages config.ini.
+    team_names : list
+        Names of the teams, in the same order as the paths. Defaults to 0, 1, ...
+    battle_type : str
+        Type of battle that is to be run. Possible options: comparison, instance_sizes and all
+        battle types returned by get_battle_types(), e.g. iterated and averaged.
+    iterations : int
+        Number of battles between each pair of teams.
+    approximation_ratio : float
+        Tolerated approximation ratio for a problem, if compatible with approximation.
+    approximation_instance_size : int
+        Instance size on which averaged battles and comparisons are run.
+    runtime_overhead : float
+        I/O overhead in seconds that is added to the configured timeouts.
+    cache_docker_containers : bool
+        Flag indicating whether to cache built docker containers.
+    fight_listeners : list
+        Callables that are passed the record of each fight as soon as it is finished.
+    seed : int
+        Seed from which the seeds passed to the generators are derived. Chosen randomly if None.
+    log_directory : str
+        Directory to which the full stdout and stderr of every container are written, if set.
+    failing_instances_directory : str
+        Directory to which the first instance that each solver failed on in
+        each iterated battle is written, if set.
+    jobs : int
+        Maximal number of fights of an averaged battle that are run in parallel.
+    checkpoint : str
+        Path to a state file that is rewritten after every fight, such that
+        the match can be resumed if it is interrupted. Defaults to resume.
+    resume : str
+        Path to the state file of an interrupted match. Its fights are not run
+        again and the match continues with its seed.
+    instance_sizes : list
+        If the battle type is 'instance_sizes', the instance sizes on which
+        iterations many fights are held between each pair of teams. Every size
+        needs to lie between the n_start of the problem and the iteration cap.
+
+    Returns
+    -------
+    BattleResult
+        The results of the match.
+
+    Raises
+    ------
+    BattleSetupError
+        If the arguments are inconsistent, a ConfigError if the config file is
+        invalid, or a ProblemLoadError if the problem could not be imported.
+    BuildError
+        If the docker containers of the match could not be built.
+    """
+    if not isinstance(problem, Problem):
+        problem = Problem.from_file(str(problem))
+
+    if battle_type not in ['comparison', 'instance_sizes'] and battle_type not in get_battle_types():
+        raise BattleSetupError('Unrecognized battle type given: "{}"'.format(battle_type))
+
+    if not isinstance(jobs, int) or jobs < 1:
+        raise BattleSetupError('Expected the number of jobs to be a positive integer.')
+
+    resumed_fights = None
+    if checkpoint or resume:
+        if battle_type == 'comparison':
+            raise BattleSetupError('Comparisons of solvers can not be checkpointed or resumed.')
+    if resume:
+        try:
+            resumed_checkpoint = Checkpoint.load(resume)
+        except ValueError as e:
+            raise BattleSetupError(str(e)) from e
+        if resumed_checkpoint.battle_type != battle_type:
+            raise BattleSetupError('The resumed match is of battle type {} instead of {}.'
+                             .format(resumed_checkpoint.battle_type, battle_type))
+        if seed is not None and seed != resumed_checkpoint.seed:
+            raise BattleSetupError('The resumed match has the seed {} instead of {}.'.format(resumed_checkpoint.seed, seed))
+        seed = resumed_checkpoint.seed
+        resumed_fights = resumed_checkpoint.fights
+
+    generator_paths = generator_path if isinstance(generator_path, list) else [generator_path]
+    solver_paths = solver_path if isinstance(solver_path, list) else [solver_path]
+    if team_names is None:
+        team_names = [str(i) for i in range(len(solver_paths))]
+
+    if len(generator_paths) != len(solver_paths) or len(solver_paths) != len(team_names):
+        raise BattleSetupError('The number of provided generator paths ({}), solver paths ({}) and team names ({}) is not equal!'
+                         .format(len(generator_paths), len(solver_paths), len(team_names)))
+
+    if config is None:
+        config = os.path.join(os.path.dirname(os.path.abspath(algobattle.__file__)), 'config', 'config.ini')
+
+    if battle_type == 'instance_sizes':
+        _check_instance_sizes(problem, config, instance_sizes)
+    elif battle_type != 'comparison':
+        # Invalid options of the battle type are reported before any docker image is built
+        config_parser = load_config(config)
+        try:
+            get_battle_types()[battle_type](config_parser)
+        except ConfigError:
+            raise
+        except ValueError as e:
+            raise BattleSetupError(str(e)) from e
+
+    teams = [Team(team_names[i], generator_paths[i], solver_paths[i]) for i in range(len(team_names))]
+
+    # Only the fights of averaged battles run in parallel, the resources
+    # of the host are checked against the number of fights that actually do.
+    if battle_type != 'averaged':
+        jobs = 1
+
+    match = Match(problem, config, teams, runtime_overhead=runtime_overhead, approximation_ratio=approximation_ratio,
+                  cache_docker_containers=cache_docker_containers, seed=seed, log_directory=log_directory,
+                  failing_instances_directory=failing_instances_directory, jobs=jobs, resumed_fights=resumed_fights)
+    if not match.build_successful:
+        raise BuildError('Building the match object failed!')
+
+    fights = []
+    match.fight_listeners.append(fights.append)
+    if checkpoint or resume:
+        checkpoint_writer = Checkpoint(checkpoint or resume, match.seed, battle_type, list(resumed_fights or []))
+        checkpoint_writer.save()
+        match.fight_listeners.append(checkpoint_writer.add_fight)
+    match.fight_listeners += fight_listeners or []
+
+    if battle_type == 'comparison':
+        generating_teams = [team.name for team in teams if team.generator_path]
+        results = match.run_solver_comparison(iterations, approximation_instance_size, generating_teams)
+    elif battle_type == 'instance_sizes':
+        results = match.run_on_instance_sizes(instance_sizes, iterations)
+    else:
+        results = match.run(battle_type, iterations, approximation_instance_size=approximation_instance_size)
+
+    return BattleResult(battle_type, iterations, match.team_names, results, fights, seed=match.seed, config=match.config,
+                        failing_instances=match.failing_instances, images=match.images,
+                        phase_times=match.phase_times)
+
+
+def _check_instance_sizes(problem: Problem, config: str, instance_sizes: list) -> None:
+    """Raise a ValueError if instance sizes are not supported by a problem under a configuration."""
+    if not instance_sizes:
+        raise BattleSetupError('Expected at least one instance size to be given.')
+
+    config_parser = load_config(config)
+    iteration_cap = read_run_parameter(config_parser, 'iteration_cap', fallback=None)
+
+    for instance_size in instance_sizes:
+        if not isinstance(instance_size, int) or instance_size < problem.n_start:
+            raise BattleSetupError('The instance size {} is smaller than the smallest instance size {} of the problem.'
+                             .format(instance_size, problem.n_start))
+        if iteration_cap is not None and instance_size > iteration_cap:
+            raise BattleSetupError('The instance size {} exceeds the iteration cap {} of the configuration.'
+                             .format(instance_size, iteration_cap))
+
+
+def run_solver_on_instance(problem, solver_path: str, instance_path: str, instance_size: int, *,
+                           config: str = None, runtime_overhead: float = 0,
+                           cache_docker_containers: bool = True) -> float:
+    """Build a solver and run it on an instance that is given as a file.
+
+    The file is expected to contain the instance and a certificate in the
+    same format as the output of a generator of the problem.
+
+    Parameters
+    ----------
+    problem : Problem or str
+        The problem object or the path to the problem directory.
+    solver_path : str
+        Path to the solver.
+    instance_path : str
+        Path to the file containing the instance and certificate.
+    instance_size : int
+        Instance size with which the instance and the solution are checked.
+    config : str
+        Path to a .ini, .toml or .yaml configuration file. Defaults to the packages config.ini.
+    runtime_overhead : float
+        I/O overhead in seconds that is added to the configured timeouts.
+    cache_docker_containers : bool
+        Flag indicating whether to cache built docker containers.
+
+    Returns
+    -------
+    float
+        Returns the approximation ratio of the solver against the certificate.
+
+    Raises
+    ------
+    BattleSetupError
+        If the instance is unreadable or malformed, a ConfigError if the config
+        file is invalid, or a ProblemLoadError if the problem could not be imported.
+    BuildError
+        If the docker container of the solver could not be built.
+    """
+    if not isinstance(problem, Problem):
+        problem = Problem.from_file(str(problem))
+
+    if problem.interactive:
+        raise BattleSetupError('Interactive problems have no instances that a solver could be run on.')
+
+    if not isinstance(instance_size, int) or not instance_size > 0:
+        raise BattleSetupError('Expected the instance size to be a positive integer.')
+
+    if config is None:
+        config = os.path.join(os.path.dirname(os.path.abspath(algobattle.__file__)), 'config', 'config.ini')
+
+    try:
+        with open(instance_path, 'rb') as instance_file:
+            encoded_instance = instance_file.read()
+    except OSError as e:
+        raise BattleSetupError('The given instance "{}" could not be read: {}'.format(instance_path, e)) from e
+
+    team = Team('0', None, solver_path)
+    match = Match(problem, config, [team], runtime_overhead=runtime_overhead,
+                  cache_docker_containers=cache_docker_containers)
+    if not match.build_successful:
+        raise BuildError('Building the match object failed!')
+
+    approximation_ratio = match.run_on_instance(encoded_instance, instance_size, team.name,
+                                                instance_name=os.path.basename(instance_path))
+    if approximation_ratio is None:
+        raise BattleSetupError('The given instance "{}" is malformed!'.format(instance_path))
+
+    return approximation_ratio
+
+
+def check_battle_setup(problem, generator_path, solver_path, *, config: str = None, team_names: list = None,
+                       build: bool = False) -> list:
+    """Check that a match could be run with the given problem, programs and configuration, without running it.
+
+    Parameters
+    ----------
+    problem : Problem or str
+        The problem object or the path to the problem directory.
+    generator_path : str or list
+        Path to the generator, or a list of generator paths for multiple teams.
+        A path may be None for teams without a generator.
+    solver_path : str or list
+        Path to the solver, or a list of solver paths for multiple teams.
+    config : str
+        Path to a .ini, .toml or .yaml configuration file. Defaults to the packages config.ini.
+    team_names : list
+        Names of the teams, in the order of the paths. The docker images
+        are tagged with these names when building. Defaults to "0", "1", ...
+    build : bool
+        Flag indicating whether to additionally build the docker containers
+        of all programs, if no other issue was found.
+
+    Returns
+    -------
+    list
+        Descriptions of all issues that were found, empty if there are none.
+    """
+    issues = []
+
+    if not isinstance(problem, Problem):
+        try:
+            problem = Problem.from_file(str(problem))
+        except ProblemLoadError as e:
+            issues.append(str(e))
+
+    if config is None:
+        config = os.path.join(os.path.dirname(os.path.abspath(algobattle.__file__)), 'config', 'config.ini')
+    config_parser = configparser.ConfigParser()
+    if not os.path.isfile(config):
+        issues.append('The config file "{}" could not be read.'.format(config))
+    else:
+        try:
+            config_parser = load_config(config)
+        except ConfigError as e:
+            issues.append(str(e))
+    if config_parser.has_section('run_parameters'):
+        for key in REQUIRED_RUN_PARAMETERS:
+            try:
+                read_run_parameter(config_parser, key)
+            except ConfigError as e:
+                issues.append(str(e))
+    else:
+        issues.append('The config file "{}" has no section run_parameters.'.format(config))
+
+    generator_paths = generator_path if isinstance(generator_path, list) else [generator_path]
+    solver_paths = solver_path if isinstance(solver_path, list) else [solver_path]
+    for role, path in [('generator', path) for path in generator_paths if path] + [('solver', path) for path in solver_paths]:
+        if not os.path.isdir(path):
+            issues.append('The build context of the {} "{}" is not a directory.'.format(role, path))
+        elif not os.path.isfile(os.path.join(path, 'Dockerfile')):
+            issues.append('The build context of the {} "{}" contains no Dockerfile.'.format(role, path))
+
+    if team_names is None:
+        team_names = [str(i) for i in range(len(solver_paths))]
+    if len(team_names) != len(solver_paths):
+        issues.append('The number of provided solver paths ({}) and team names ({}) is not equal.'
+                      .format(len(solver_paths), len(team_names)))
+
+    if build and not issues:
+        teams = [Team(team_names[i], generator_paths[i] if i < len(generator_paths) else None, solver_paths[i])
+                 for i in range(len(solver_paths))]
+        match = Match(problem, config, teams)
+        if not match.build_successful:
+            issues.append('Building the docker containers failed, see the log for details.')
+
+    return issues
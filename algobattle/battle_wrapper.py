@@ -0,0 +1,403 @@
+"""Battle types and the discovery of battle types that are registered by plugins."""
+from abc import ABC, abstractmethod
+import configparser
+import logging
+import statistics
+
+logger = logging.getLogger('algobattle.battle_wrapper')
+
+ENTRY_POINT_GROUP = 'algobattle.battle_types'
+
+AGGREGATION_METHODS = ['mean', 'median', 'trimmed_mean']
+
+
+class BattleWrapper(ABC):
+    """BattleWrapper class, responsible for executing a single battle between a generating and a solving team.
+
+    Custom battle types can be added without changing this package by
+    subclassing this class and registering the subclass as an entry point
+    of the group 'algobattle.battle_types', or by passing it to
+    register_battle_type. Battle types build upon the public methods of the
+    Match class, e.g. run_fight, iterated_battle and averaged_battle.
+
+    Battle types are instantiated with the configuration of the match, from
+    which they may read additional options. They work on a copy of it, such
+    that the configuration of the match is left unchanged.
+    """
+
+    def __init__(self, config: configparser.ConfigParser = None) -> None:
+        self.config = configparser.ConfigParser()
+        if config is not None:
+            self.config.read_dict(config)
+        if not self.config.has_section('run_parameters'):
+            self.config.add_section('run_parameters')
+
+    @property
+    @abstractmethod
+    def name(self):
+        raise NotImplementedError
+
+    @abstractmethod
+    def run(self, match) -> any:
+        """Execute one battle between the generating and the solving team of a match.
+
+        Parameters
+        ----------
+        match : Match
+            The match whose generating_team and solving_team are set.
+
+        Returns
+        -------
+        any
+            The result of the battle.
+        """
+        raise NotImplementedError
+
+    @abstractmethod
+    def valuation(self, battle_result: any) -> float:
+        """Calculate how well the solving team performed in a battle.
+
+        Parameters
+        ----------
+        battle_result : any
+            The result of the battle as returned by the run method.
+
+        Returns
+        -------
+        float
+            A nonnegative number, higher meaning a better result of the solving team.
+        """
+        raise NotImplementedError
+
+
+_battle_types = dict()
+
+
+def register_battle_type(battle_type: type) -> type:
+    """Make a battle type available under its name, usable as a class decorator.
+
+    If the name is already taken by another battle type, the battle type
+    that was registered first is kept.
+
+    Parameters
+    ----------
+    battle_type : type
+        A subclass of BattleWrapper with a name.
+
+    Returns
+    -------
+    type
+        The given battle type.
+    """
+    if not isinstance(battle_type, type) or not issubclass(battle_type, BattleWrapper) \
+            or not isinstance(battle_type.name, str):
+        raise ValueError('Expected a subclass of BattleWrapper with a name, received: {}'.format(battle_type))
+    registered_battle_type = _battle_types.setdefault(battle_type.name, battle_type)
+    if registered_battle_type is not battle_type:
+        logger.warning('The battle type name "{}" is already taken by {}, ignoring {}.'
+                       .format(battle_type.name, registered_battle_type, battle_type))
+    return battle_type
+
+
+@register_battle_type
+class IteratedBattleWrapper(BattleWrapper):
+    """Battle type searching for the biggest instance size that the solving team is still able to solve.
+
+    The instance sizes of the fights are chosen by the step strategy that is
+    configured by the options step_strategy and step_size of the run parameters.
+    """
+
+    name = 'iterated'
+
+    def __init__(self, config: configparser.ConfigParser = None) -> None:
+        super().__init__(config)
+        self.step_strategy = self.config['run_parameters'].get('step_strategy', fallback='default')
+        self.step_size = self.config['run_parameters'].getint('step_size', fallback=1)
+
+        if self.step_strategy not in STEP_STRATEGIES:
+            raise ValueError('Unrecognized step strategy given: "{}"'.format(self.step_strategy))
+        if self.step_size < 1:
+            raise ValueError('The step size needs to be at least 1.')
+
+    def create_step_strategy(self, n_start: int, iteration_cap: int):
+        """Create a new instance of the configured step strategy for a single battle.
+
+        Parameters
+        ----------
+        n_start : int
+            The smallest instance size of the problem.
+        iteration_cap : int
+            The instance size cap of the battle.
+
+        Returns
+        -------
+        StepStrategy
+            The step strategy.
+        """
+        return STEP_STRATEGIES[self.step_strategy](n_start, iteration_cap, self.step_size)
+
+    def run(self, match) -> int:
+        return match.iterated_battle(self.create_step_strategy(match.problem.n_start, match.iteration_cap))
+
+    def valuation(self, battle_result: int) -> float:
+        return battle_result
+
+
+@register_battle_type
+class AveragedBattleWrapper(BattleWrapper):
+    """Battle type determining the average solution quality of the solving team on a fixed instance size.
+
+    The approximation ratios of all fights are combined as configured by the
+    options aggregation_method and trim_fraction of the run parameters.
+    """
+
+    name = 'averaged'
+
+    def __init__(self, config: configparser.ConfigParser = None) -> None:
+        super().__init__(config)
+        self.aggregation_method = self.config['run_parameters'].get('aggregation_method', fallback='mean')
+        self.trim_fraction = self.config['run_parameters'].getfloat('trim_fraction', fallback=0.1)
+
+        if self.aggregation_method not in AGGREGATION_METHODS:
+            raise ValueError('Unrecognized aggregation method given: "{}"'.format(self.aggregation_method))
+        if not 0 <= self.trim_fraction < 0.5:
+            raise ValueError('The trim fraction needs to be at least 0 and less than 0.5.')
+
+    def run(self, match) -> list:
+        return match.averaged_battle()
+
+    def valuation(self, battle_result: list) -> float:
+        # The valuation of an averaged battle
+        # is the number of successfully executed battles divided by
+        # the aggregated competitive ratio of successful battles,
+        # to account for failures on execution. A higher number
+        # thus means a better overall result.
+        aggregated_ratio = aggregate(battle_result, self.aggregation_method, self.trim_fraction)
+        if aggregated_ratio == 0:
+            return 0.0
+        return len(battle_result) / aggregated_ratio
+
+
+class StepStrategy(ABC):
+    """StepStrategy class, chooses the instance sizes of the fights of an iterated battle.
+
+    A battle starts at the instance size n_start and ends once next_size
+    returns None. A solving team that would be given an instance of at least
+    the iteration cap is declared to have solved the cap.
+    """
+
+    @property
+    @abstractmethod
+    def name(self):
+        raise NotImplementedError
+
+    def __init__(self, n_start: int, iteration_cap: int, step_size: int = 1) -> None:
+        self.n_start = n_start
+        self.iteration_cap = iteration_cap
+        self.step_size = step_size
+        self.maximum_reached_n = 0
+
+    @abstractmethod
+    def next_size(self, n: int, solved: bool) -> int:
+        """Choose the instance size of the next fight.
+
+        Parameters
+        ----------
+        n : int
+            The instance size of the last fight.
+        solved : bool
+            Boolean indicating whether the solving team solved the last instance.
+
+        Returns
+        -------
+        int
+            The instance size of the next fight, None if the battle is over.
+        """
+        raise NotImplementedError
+
+    def _capped(self, n: int) -> int:
+        if n >= self.iteration_cap:
+            self.maximum_reached_n = self.iteration_cap
+            return None
+        return n
+
+
+class DefaultStepStrategy(StepStrategy):
+    """Step strategy increasing the step size quadratically, resetting it after each failure.
+
+    The base increment value is multiplied with the square of the iterations
+    since the last unsolvable instance. Only once the solver fails after the
+    multiplier is reset, it counts as failed. Since this would heavily favour
+    probabilistic algorithms (That may have only failed by chance and are able
+    to solve a certain instance size on a second try), we cap the maximum
+    solution size by the first value that an algorithm has failed on.
+    """
+
+    name = 'default'
+
+    def __init__(self, n_start: int, iteration_cap: int, step_size: int = 1) -> None:
+        super().__init__(n_start, iteration_cap, step_size)
+        self.i = 0
+        self.n_cap = iteration_cap
+
+    def next_size(self, n: int, solved: bool) -> int:
+        if not solved and self.i > 1:
+            # The step size increase was too aggressive, take it back and reset the increment multiplier
+            logger.info('Setting the solution cap to {}...'.format(n))
+            self.n_cap = n
+            n -= self.i * self.i
+            self.i = 0
+            solved = True
+        elif n > self.maximum_reached_n and solved:
+            # We solved an instance of bigger size than before
+            self.maximum_reached_n = n
+
+        if n + 1 == self.n_cap:
+            return None
+
+        self.i += 1
+        n += self.i * self.i
+
+        if n >= self.n_cap and self.n_cap != self.iteration_cap:
+            # We have failed at this value of n already, reset the step size!
+            n -= self.i * self.i - 1
+            self.i = 1
+        elif n >= self.n_cap and self.n_cap == self.iteration_cap:
+            self.maximum_reached_n = self.iteration_cap
+            return None
+        return n if solved else None
+
+
+class LinearStepStrategy(StepStrategy):
+    """Step strategy increasing the instance size by the step size until the first failure."""
+
+    name = 'linear'
+
+    def next_size(self, n: int, solved: bool) -> int:
+        if not solved:
+            return None
+        self.maximum_reached_n = max(self.maximum_reached_n, n)
+        return self._capped(n + self.step_size)
+
+
+class DoublingStepStrategy(StepStrategy):
+    """Step strategy doubling the instance size until the first failure."""
+
+    name = 'doubling'
+
+    def next_size(self, n: int, solved: bool) -> int:
+        if not solved:
+            return None
+        self.maximum_reached_n = max(self.maximum_reached_n, n)
+        return self._capped(max(2 * n, n + 1))
+
+
+class BinarySearchStepStrategy(StepStrategy):
+    """Step strategy doubling the instance size until the first failure, followed by a binary search.
+
+    The binary search takes place between the biggest solved and the smallest
+    failed instance size, such that the exact breaking point of a solver is
+    found within a logarithmic number of fights.
+    """
+
+    name = 'binary_search'
+
+    def __init__(self, n_start: int, iteration_cap: int, step_size: int = 1) -> None:
+        super().__init__(n_start, iteration_cap, step_size)
+        self.failed_n = None
+
+    def next_size(self, n: int, solved: bool) -> int:
+        if solved:
+            self.maximum_reached_n = max(self.maximum_reached_n, n)
+        elif self.failed_n is None or n < self.failed_n:
+            self.failed_n = n
+
+        if self.failed_n is None:
+            # The last size below the cap is tried before the solver is declared to have reached the cap
+            return self._capped(min(max(2 * n, n + 1), max(self.iteration_cap - 1, n + 1)))
+
+        # Instances smaller than n_start are never tried
+        lower = max(self.maximum_reached_n, self.n_start - 1)
+        if self.failed_n - lower <= 1:
+            return None
+        return (lower + self.failed_n) // 2
+
+
+STEP_STRATEGIES = {step_strategy.name: step_strategy for step_strategy in
+                   [DefaultStepStrategy, LinearStepStrategy, DoublingStepStrategy, BinarySearchStepStrategy]}
+
+
+def aggregate(values: list, aggregation_method: str = 'mean', trim_fraction: float = 0.1) -> float:
+    """Combine a list of values into a single value.
+
+    Parameters
+    ----------
+    values : list
+        A nonempty list of numbers.
+    aggregation_method : str
+        How to combine the values. Possible options: mean, median, trimmed_mean.
+    trim_fraction : float
+        If aggregation_method is trimmed_mean, the fraction of the smallest
+        and of the largest values that are discarded before taking the mean.
+
+    Returns
+    -------
+    float
+        The aggregated value.
+    """
+    if aggregation_method == 'median':
+        return statistics.median(values)
+    elif aggregation_method == 'trimmed_mean':
+        trimmed_count = int(len(values) * trim_fraction)
+        values = sorted(values)[trimmed_count:len(values) - trimmed_count]
+    elif aggregation_method != 'mean':
+        raise ValueError('Unrecognized aggregation method given: "{}"'.format(aggregation_method))
+    return sum(values) / len(values)
+
+
+_entry_points_loaded = False
+
+
+def _load_entry_points() -> None:
+    """Import and register all battle types that are registered as entry points, once."""
+    global _entry_points_loaded
+    if _entry_points_loaded:
+        return
+    _entry_points_loaded = True
+
+    try:
+        from importlib.metadata import entry_points
+    except ImportError:
+        return
+
+    all_entry_points = entry_points()
+    if hasattr(all_entry_points, 'select'):
+        battle_type_entry_points = all_entry_points.select(group=ENTRY_POINT_GROUP)
+    else:
+        battle_type_entry_points = all_entry_points.get(ENTRY_POINT_GROUP, [])
+
+    for entry_point in battle_type_entry_points:
+        try:
+            battle_type = entry_point.load()
+        except Exception as e:
+            logger.warning('Loading the battle type "{}" failed with the following exception: "{}"'
+                           .format(entry_point.name, e))
+            continue
+        if not isinstance(battle_type, type) or not issubclass(battle_type, BattleWrapper) \
+                or not isinstance(battle_type.name, str):
+            logger.warning('The entry point "{}" does not refer to a subclass of BattleWrapper, ignoring it.'
+                           .format(entry_point.name))
+            continue
+        register_battle_type(battle_type)
+
+
+def get_battle_types() -> dict:
+    """Return all available battle types, including those registered by plugins.
+
+    Returns
+    -------
+    dict
+        A mapping between the names of the battle types and their BattleWrapper subclasses.
+    """
+    _load_entry_points()
+    return dict(_battle_types)
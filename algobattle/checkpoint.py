@@ -0,0 +1,74 @@
+"""Checkpoints of the completed fights of a match, such that interrupted matches can be resumed."""
+import json
+import logging
+import os
+
+logger = logging.getLogger('algobattle.checkpoint')
+
+
+class Checkpoint:
+    """Checkpoint class, persists the seed and the records of all completed fights of a match to a state file.
+
+    An instance is registered as a fight listener of a match, such that the
+    state file is rewritten after every fight. Since the seeds of all fights
+    are derived from the seed of the match, a match that is started with the
+    seed and the fights of a checkpoint replays the completed fights without
+    running them and continues with identical results.
+    """
+
+    def __init__(self, path: str, seed: int, battle_type: str, fights: list = None) -> None:
+        self.path = path
+        self.seed = seed
+        self.battle_type = battle_type
+        self.fights = fights or []
+        self.next_fight = 0
+
+    @classmethod
+    def load(cls, path: str):
+        """Read a checkpoint from a state file.
+
+        Parameters
+        ----------
+        path : str
+            Path to the state file.
+
+        Returns
+        -------
+        Checkpoint
+            The checkpoint.
+
+        Raises
+        ------
+        ValueError
+            If the file can not be read or is not a valid state file.
+        """
+        try:
+            with open(path) as state_file:
+                state = json.load(state_file)
+            return cls(path, int(state['seed']), state['battle_type'], list(state['fights']))
+        except (OSError, ValueError, TypeError, KeyError) as e:
+            raise ValueError('The state file "{}" could not be read: {}'.format(path, e))
+
+    def save(self) -> None:
+        """Atomically write the checkpoint to its state file."""
+        temporary_path = self.path + '.tmp'
+        with open(temporary_path, 'w') as state_file:
+            json.dump({'seed': self.seed, 'battle_type': self.battle_type, 'fights': self.fights}, state_file)
+        os.replace(temporary_path, self.path)
+
+    def add_fight(self, fight_record: dict) -> None:
+        """Add the record of a completed fight and write the checkpoint.
+
+        The records of the fights that the checkpoint already contains are
+        skipped, as they are replayed first when a match is resumed.
+
+        Parameters
+        ----------
+        fight_record : dict
+            The record of the fight as provided by the Match class.
+        """
+        self.next_fight += 1
+        if self.next_fight <= len(self.fights):
+            return
+        self.fights.append(fight_record)
+        self.save()
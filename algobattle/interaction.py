@@ -0,0 +1,106 @@
+"""Processes that exchange messages with the framework over several rounds."""
+import logging
+import queue
+import subprocess
+import tempfile
+import threading
+import timeit
+
+logger = logging.getLogger('algobattle.interaction')
+
+
+class InteractiveProcess:
+    """InteractiveProcess class, runs a program that stays alive while exchanging messages over stdio.
+
+    Each message is a single line. The time that the framework spends waiting
+    for messages of the program is summed up and bounded by the timeout, such
+    that a program can not stall an interaction indefinitely.
+    """
+
+    def __init__(self, run_command: list, timeout: float) -> None:
+        self.timeout = timeout
+        self.waiting_time = 0.0
+        self._start_time = timeit.default_timer()
+        self.transcript = b''
+        self._stderr = tempfile.TemporaryFile()
+        self._messages = queue.Queue()
+        self._process = subprocess.Popen(run_command, stdin=subprocess.PIPE, stdout=subprocess.PIPE,
+                                         stderr=self._stderr)
+        self._reader = threading.Thread(target=self._read_messages, daemon=True)
+        self._reader.start()
+
+    def _read_messages(self) -> None:
+        for line in self._process.stdout:
+            self._messages.put(line)
+        self._messages.put(None)
+
+    def send(self, message: bytes) -> bool:
+        """Send a single message to the program.
+
+        Parameters
+        ----------
+        message : bytes
+            The message, a trailing newline is added if it is missing.
+
+        Returns
+        -------
+        Bool
+            Boolean indicating whether the message could be delivered.
+        """
+        if not message.endswith(b'\n'):
+            message += b'\n'
+        try:
+            self._process.stdin.write(message)
+            self._process.stdin.flush()
+        except (BrokenPipeError, ValueError):
+            logger.warning('The program terminated before a message could be sent to it!')
+            return False
+        return True
+
+    def receive(self) -> bytes:
+        """Wait for the next message of the program.
+
+        Returns
+        -------
+        bytes
+            The message without its trailing newline, None if the program
+            terminated or exceeded its timeout.
+        """
+        remaining_time = self.timeout - self.waiting_time
+        start_time = timeit.default_timer()
+        try:
+            message = self._messages.get(timeout=max(remaining_time, 0))
+        except queue.Empty:
+            message = None
+            logger.warning('Time limit exceeded!')
+        self.waiting_time += timeit.default_timer() - start_time
+
+        if message is None:
+            return None
+        self.transcript += message
+        return message.rstrip(b'\r\n')
+
+    def stop(self) -> dict:
+        """Terminate the program.
+
+        Returns
+        -------
+        dict
+            The messages that the program sent, its stderr, its exit code and
+            the wall-clock time that it ran under the keys 'stdout', 'stderr',
+            'exit_code' and 'wall_time'.
+        """
+        wall_time = round(timeit.default_timer() - self._start_time, 2)
+        self._process.kill()
+        self._process.wait()
+        self._reader.join(timeout=1)
+        try:
+            self._process.stdin.close()
+        except BrokenPipeError:
+            pass
+        self._process.stdout.close()
+        self._stderr.seek(0)
+        output_log = {'stdout': self.transcript, 'stderr': self._stderr.read(), 'exit_code': self._process.returncode,
+                      'wall_time': wall_time}
+        self._stderr.close()
+        return output_log
@@ -0,0 +1,65 @@
+"""Writer for streaming the progress of a match as JSON Lines."""
+import json
+import logging
+
+logger = logging.getLogger('algobattle.jsonl')
+
+
+class JsonlWriter:
+    """JsonlWriter class, writes one JSON object per line for each event of a match.
+
+    Every line is flushed immediately, such that consumers are able to react
+    to the progress of a match while it is still running.
+    """
+
+    def __init__(self, stream) -> None:
+        self.stream = stream
+
+    def write_event(self, event: dict) -> None:
+        """Serialize a single event and flush it to the underlying stream.
+
+        Parameters
+        ----------
+        event : dict
+            The event that is to be written. Needs to be serializable as JSON.
+        """
+        self.stream.write(json.dumps(event) + '\n')
+        self.stream.flush()
+
+    def write_fight(self, fight_record: dict) -> None:
+        """Write the record of a single fight, discriminated by the type 'fight'.
+
+        Parameters
+        ----------
+        fight_record : dict
+            The record of the fight as provided by the Match class.
+        """
+        self.write_event(dict({'type': 'fight'}, **fight_record))
+
+    def write_summary(self, battle_type: str, results: dict, points: dict = None, seed: int = None,
+                      images: dict = None) -> None:
+        """Write the summary of a match, discriminated by the type 'summary'.
+
+        Parameters
+        ----------
+        battle_type : str
+            Type of battle that was held.
+        results : dict
+            dict containing the results of match.run().
+        points : dict
+            A mapping between team names and their achieved points, if calculated.
+        seed : int
+            The seed of the match.
+        images : dict
+            A mapping between the names of the docker images and the information
+            identifying them, as collected by the Match class.
+        """
+        self.write_event({
+            'type': 'summary',
+            'battle_type': battle_type,
+            'results': [{'generating_team': pair[0], 'solving_team': pair[1], 'results': pair_results}
+                        for pair, pair_results in results.items()],
+            'points': points,
+            'seed': seed,
+            'images': images
+        })
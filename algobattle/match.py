@@ -1,42 +1,102 @@
+import os
+import json
 import subprocess
+import random
+import uuid
+import gzip
+import multiprocessing
+import multiprocessing.connection
+import time
+import timeit
 
 import logging
-import configparser
 from typing import Callable
 
 import algobattle.sighandler as sigh
 from algobattle.team import Team
 from algobattle.problem import Problem
-from algobattle.util import run_subprocess
+from algobattle.battle_wrapper import get_battle_types, StepStrategy, DefaultStepStrategy
+from algobattle.interaction import InteractiveProcess
+from algobattle.verifier import Score, VerificationError
+from algobattle.util import run_subprocess, run_with_limits, hash_build_context, truncate_output, decompress_output, \
+    is_docker_daemon_error, load_config, read_base_image, read_run_parameter, ConfigError
 
 logger = logging.getLogger('algobattle.match')
 
+CONTEXT_HASH_LABEL = 'algobattle.context_hash'
+
+PROFILE_PHASES = ['build', 'generate', 'solve', 'check_semantics', 'calculate_score']
+
 
 class Match:
     """Match class, provides functionality for setting up and executing battles between given teams."""
 
     def __init__(self, problem: Problem, config_path: str, teams: list,
-                 runtime_overhead=0, approximation_ratio=1.0, cache_docker_containers=True) -> None:
+                 runtime_overhead=0, approximation_ratio=1.0, cache_docker_containers=True, seed=None,
+                 log_directory=None, failing_instances_directory=None, jobs=1,
+                 resumed_fights=None) -> None:
 
-        config = configparser.ConfigParser()
         logger.debug('Using additional configuration options from file "%s".', config_path)
-        config.read(config_path)
-
-        self.timeout_build           = int(config['run_parameters']['timeout_build']) + runtime_overhead
-        self.timeout_generator       = int(config['run_parameters']['timeout_generator']) + runtime_overhead
-        self.timeout_solver          = int(config['run_parameters']['timeout_solver']) + runtime_overhead
-        self.space_generator         = int(config['run_parameters']['space_generator'])
-        self.space_solver            = int(config['run_parameters']['space_solver'])
-        self.cpus                    = int(config['run_parameters']['cpus'])
-        self.iteration_cap           = int(config['run_parameters']['iteration_cap'])
-        self.aproximation_iterations = int(config['run_parameters']['aproximation_iterations'])
+        config = load_config(config_path)
+
+        self.timeout_build           = read_run_parameter(config, 'timeout_build') + runtime_overhead
+        self.timeout_generator       = read_run_parameter(config, 'timeout_generator') + runtime_overhead
+        self.timeout_solver          = read_run_parameter(config, 'timeout_solver') + runtime_overhead
+        self.space_generator         = read_run_parameter(config, 'space_generator')
+        self.space_solver            = read_run_parameter(config, 'space_solver')
+        self.cpus                    = read_run_parameter(config, 'cpus', float, fallback=1)
+        self.cpus_generator          = read_run_parameter(config, 'cpus_generator', float, fallback=self.cpus)
+        self.cpus_solver             = read_run_parameter(config, 'cpus_solver', float, fallback=self.cpus)
+        self.iteration_cap           = read_run_parameter(config, 'iteration_cap')
+        self.aproximation_iterations = read_run_parameter(config, 'aproximation_iterations')
+        self.timeout_verification    = read_run_parameter(config, 'timeout_verification', fallback=60)
+        self.space_verification      = read_run_parameter(config, 'space_verification', fallback=2000)
+        self.output_capture_limit    = read_run_parameter(config, 'output_capture_limit', fallback=4096)
+        self.docker_retries          = read_run_parameter(config, 'docker_retries', fallback=3)
+        self.docker_retry_delay      = read_run_parameter(config, 'docker_retry_delay', float, fallback=1)
+        for key in ['docker_retries', 'docker_retry_delay']:
+            if getattr(self, key) < 0:
+                raise ConfigError('The option "run_parameters.{}" of the config file may not be negative.'.format(key))
         self.problem = problem
         self.config = config
         self.approximation_ratio = approximation_ratio
+        self.jobs = jobs
+        # Records of the fights of an interrupted match, which are replayed instead of run.
+        self.resumed_fights = resumed_fights or []
+
+        # The seeds passed to the generators are drawn from a generator seeded
+        # with the seed of the match, such that a match can be replayed exactly.
+        self.seed = seed if seed is not None else random.randrange(2**32)
+        self.seed_generator = random.Random(self.seed)
+        logger.info('Using the seed {} for this match.'.format(self.seed))
 
         self.generating_team = None
         self.solving_team = None
+        self.battle_type = None
+        self.fight_listeners = []
+        self.fight_error = None
+        self.fight_failure = None
+        # The program that is to blame for a failed fight, either generator or solver
+        self.fight_failed_role = None
+        self.fight_score_components = None
+        self.fight_rejection_reason = None
+        self.fight_seed = None
+        self.fight_instance = None
+        self.failing_instances = []
+        self.failing_instances_directory = failing_instances_directory
+        self.fight_logs = dict()
+        self.fight_index = 0
+        self.images = dict()
+        self.log_directory = log_directory
+
+        # Coarse wall-clock times spent in each phase of the match, summed over all fights
+        self.phase_times = {phase: 0.0 for phase in PROFILE_PHASES}
+        start_time = timeit.default_timer()
         self.build_successful = self._build(teams, cache_docker_containers)
+        self.phase_times['build'] += timeit.default_timer() - start_time
+
+        if self.build_successful and not self._check_resource_limits():
+            self.build_successful = False
 
         if approximation_ratio != 1.0 and not problem.approximable:
             logger.error('The given problem is not approximable and can only be run with an approximation ratio of 1.0!')
@@ -47,10 +107,12 @@ class Match:
             "run",
             "--rm",
             "--network", "none",
-            "-i",
-            "--memory=" + str(self.space_solver) + "mb",
-            "--cpus=" + str(self.cpus)
+            "-i"
         ]
+        self.generator_run_options = ["--memory=" + str(self.space_generator) + "mb",
+                                      "--cpus=" + str(self.cpus_generator)]
+        self.solver_run_options = ["--memory=" + str(self.space_solver) + "mb",
+                                   "--cpus=" + str(self.cpus_solver)]
 
     def build_successful(function: Callable) -> Callable:
         """Ensure that internal methods are only callable after a successful build."""
@@ -84,6 +146,156 @@ class Match:
                 return function(self, *args, **kwargs)
         return wrapper
 
+    def fight_replayed(function: Callable) -> Callable:
+        """Replay the recorded fights of a resumed match instead of running them, without requiring docker."""
+        def wrapper(self, instance_size, *args, **kwargs):
+            if self.fight_index < len(self.resumed_fights):
+                return self._replay_fight(instance_size)
+            return function(self, instance_size, *args, **kwargs)
+        return wrapper
+
+    def fight_recorded(function: Callable) -> Callable:
+        """Pass a record of each executed fight to all registered fight listeners."""
+        def wrapper(self, instance_size, *args, **kwargs):
+            start_time = time.time()
+            start_timer = timeit.default_timer()
+            self.fight_error = None
+            self.fight_failure = None
+            self.fight_failed_role = None
+            self.fight_score_components = None
+            self.fight_rejection_reason = None
+            self.fight_logs.pop('solver', None)
+            approximation_ratio = function(self, instance_size, *args, **kwargs)
+            fight_record = {
+                'battle_type': self.battle_type,
+                'instance_size': instance_size,
+                'generating_team': self.generating_team,
+                'solving_team': self.solving_team,
+                'score': approximation_ratio,
+                'score_components': self.fight_score_components,
+                'start_time': start_time,
+                'elapsed_time': round(timeit.default_timer() - start_timer, 2),
+                'seed': self.fight_seed,
+                'error': self.fight_error,
+                'failure': self.fight_failure,
+                'failed_role': self.fight_failed_role,
+                'rejection_reason': self.fight_rejection_reason,
+                'resource_limits': self.resource_limits(),
+                'logs': self._write_fight_logs(),
+                'timings': {role: {'wall_time': output_log.get('wall_time'), 'cpu_time': output_log.get('cpu_time')}
+                            for role, output_log in self.fight_logs.items()}
+            }
+            self.fight_index += 1
+            for listener in self.fight_listeners:
+                listener(fight_record)
+            return approximation_ratio
+        return wrapper
+
+    def _failure_record(self, instance_size: int, seed: int, error: str) -> dict:
+        """Create the record of a fight that could not be run to its end, e.g. because its process crashed.
+
+        Parameters
+        ----------
+        instance_size : int
+            The instance size of the fight.
+        seed : int
+            The seed that was drawn for the fight.
+        error : str
+            Description of the error.
+
+        Returns
+        -------
+        dict
+            A record of the fight with the score 0, like those passed to the fight listeners.
+        """
+        return {
+            'battle_type': self.battle_type,
+            'instance_size': instance_size,
+            'generating_team': self.generating_team,
+            'solving_team': self.solving_team,
+            'score': 0.0,
+            'score_components': None,
+            'start_time': time.time(),
+            'elapsed_time': 0.0,
+            'seed': seed,
+            'error': error,
+            'failure': None,
+            'failed_role': None,
+            'rejection_reason': None,
+            'resource_limits': self.resource_limits(),
+            'logs': {},
+            'timings': {}
+        }
+
+    def _replay_fight(self, instance_size: int) -> float:
+        """Replay the next fight of a resumed match from its record instead of running it.
+
+        The record is passed to all fight listeners and the seed generator is
+        advanced as if the fight had been run.
+
+        Parameters
+        ----------
+        instance_size : int
+            The instance size of the fight.
+
+        Returns
+        -------
+        float
+            The approximation ratio of the recorded fight.
+
+        Raises
+        ------
+        ValueError
+            If the record belongs to a different fight, e.g. because the
+            configuration of the match changed.
+        """
+        fight_record = self.resumed_fights[self.fight_index]
+        expected = (self.battle_type, instance_size, self.generating_team, self.solving_team)
+        recorded = tuple(fight_record.get(key) for key in ['battle_type', 'instance_size', 'generating_team',
+                                                           'solving_team'])
+        if recorded != expected:
+            raise ValueError('Fight {} of the resumed match does not fit this match: expected {}, recorded {}.'
+                             .format(self.fight_index, expected, recorded))
+
+        logger.info('Replaying the recorded fight {} at instance size {}.'.format(self.fight_index, instance_size))
+        # Each fight draws exactly one seed, advance the state as the recorded fight did.
+        self.seed_generator.randrange(2**32)
+        self.fight_seed = fight_record.get('seed')
+        self.fight_error = fight_record.get('error')
+        self.fight_failure = fight_record.get('failure')
+        self.fight_failed_role = fight_record.get('failed_role')
+        self.fight_instance = None
+        self.fight_index += 1
+        for listener in self.fight_listeners:
+            listener(fight_record)
+        return fight_record['score']
+
+    def _write_fight_logs(self) -> dict:
+        """Prepare the captured output of the containers of the current fight for its record.
+
+        If a log directory is set, the full output of each container is
+        additionally written to it, named by the index of the fight and the
+        role of the container.
+
+        Returns
+        -------
+        dict
+            The stdout and stderr of each container, truncated to the configured limit.
+        """
+        if self.log_directory:
+            os.makedirs(self.log_directory, exist_ok=True)
+
+        fight_logs = dict()
+        for role, output_log in self.fight_logs.items():
+            fight_logs[role] = dict()
+            for stream in ['stdout', 'stderr']:
+                fight_logs[role][stream] = truncate_output(output_log.get(stream), self.output_capture_limit)
+                if self.log_directory and output_log.get(stream) is not None:
+                    log_path = os.path.join(self.log_directory, 'fight{}_{}.{}'.format(self.fight_index, role, stream))
+                    with open(log_path, 'wb') as log_file:
+                        log_file.write(output_log[stream])
+        return fight_logs
+
     @docker_running
     def _build(self, teams: list, cache_docker_containers=True) -> bool:
         """Build docker containers for the given generators and solvers of each team.
@@ -93,7 +305,8 @@ class Match:
         teams : list
             List of Team objects.
         cache_docker_containers : bool
-            Flag indicating whether to cache built docker containers.
+            Flag indicating whether to cache built docker containers. If set,
+            containers whose build context did not change are not rebuilt.
 
         Returns
         -------
@@ -104,8 +317,7 @@ class Match:
             "docker",
             "build",
         ] + (["--no-cache"] if not cache_docker_containers else []) + [
-            "--network=host",
-            "-t"
+            "--network=host"
         ]
 
         if not isinstance(teams, list) or any(not isinstance(team, Team) for team in teams):
@@ -113,7 +325,7 @@ class Match:
             return False
 
         self.team_names = [team.name for team in teams]
-        build_commands = []
+        images = []
         if len(self.team_names) != len(list(set(self.team_names))):
             logger.error('At least one team name is used twice!')
             return False
@@ -123,10 +335,20 @@ class Match:
             self.single_player = True
 
         for team in teams:
-            build_commands.append(docker_build_base + ["solver-" + str(team.name), team.solver_path])
-            build_commands.append(docker_build_base + ["generator-" + str(team.name), team.generator_path])
-
-        for command in build_commands:
+            images.append(("solver-" + str(team.name), team.solver_path))
+            if team.generator_path:
+                images.append(("generator-" + str(team.name), team.generator_path))
+
+        for image_name, build_context in images:
+            # Images are labeled with a hash of their build context, such that
+            # unchanged programs do not need to be rebuilt for every match.
+            context_hash = hash_build_context(build_context)
+            if cache_docker_containers and self._image_context_hash(image_name) == context_hash:
+                logger.info('The build context of {} is unchanged, reusing the existing image.'.format(image_name))
+                continue
+
+            command = docker_build_base + ["--label", CONTEXT_HASH_LABEL + "=" + context_hash,
+                                           "-t", image_name, build_context]
             logger.debug('Building docker container with the following command: {}'.format(command))
             with subprocess.Popen(command, stdout=subprocess.PIPE, stderr=subprocess.PIPE) as process:
                 try:
@@ -135,16 +357,138 @@ class Match:
                 except subprocess.TimeoutExpired:
                     process.kill()
                     process.wait()
-                    logger.error('Build process for {} ran into a timeout!'.format(command[5]))
+                    logger.error('Build process for {} ran into a timeout!'.format(image_name))
                     return False
                 if process.returncode != 0:
                     process.kill()
                     process.wait()
-                    logger.error('Build process for {} failed!'.format(command[5]))
+                    logger.error('Build process for {} failed!'.format(image_name))
                     return False
 
+        for image_name, build_context in images:
+            self.images[image_name] = self._inspect_image(image_name, build_context)
+            logger.info('Using the image {} with the id {}, built from {}.'
+                        .format(image_name, self.images[image_name]['id'], self.images[image_name]['base_image']))
+
         return True
 
+    def _inspect_image(self, image_name: str, build_context: str) -> dict:
+        """Collect the information identifying exactly which docker image runs a program.
+
+        Parameters
+        ----------
+        image_name : str
+            Name of the docker image.
+        build_context : str
+            Path to the build context of the image.
+
+        Returns
+        -------
+        dict
+            The id of the image, its repository digests, the base image of its
+            Dockerfile with the id of the base image if it is available locally,
+            and a short fingerprint of its build context under the keys 'id',
+            'repo_digests', 'base_image', 'base_image_id' and 'build_fingerprint'.
+            Values that docker does not report are None.
+        """
+        image_info = {'id': None, 'repo_digests': [], 'base_image': read_base_image(build_context),
+                      'base_image_id': None, 'build_fingerprint': None}
+
+        inspect_command = ["docker", "image", "inspect", "--format", "{{json .}}", image_name]
+        process = subprocess.run(inspect_command, stdout=subprocess.PIPE, stderr=subprocess.PIPE)
+        if process.returncode == 0:
+            try:
+                inspect_output = json.loads(process.stdout.decode())
+            except ValueError:
+                inspect_output = dict()
+            # Images that were pulled rather than built locally only carry repository digests
+            image_info['id'] = inspect_output.get('Id')
+            image_info['repo_digests'] = inspect_output.get('RepoDigests') or []
+            labels = (inspect_output.get('Config') or dict()).get('Labels') or dict()
+            if labels.get(CONTEXT_HASH_LABEL):
+                image_info['build_fingerprint'] = labels[CONTEXT_HASH_LABEL][:12]
+        else:
+            logger.warning('Could not inspect the image {}!'.format(image_name))
+
+        if image_info['base_image']:
+            inspect_command = ["docker", "image", "inspect", "--format", "{{.Id}}", image_info['base_image']]
+            process = subprocess.run(inspect_command, stdout=subprocess.PIPE, stderr=subprocess.PIPE)
+            if process.returncode == 0:
+                image_info['base_image_id'] = process.stdout.decode().strip() or None
+
+        return image_info
+
+    def _image_context_hash(self, image_name: str) -> str:
+        """Return the build context hash that a docker image is labeled with.
+
+        Parameters
+        ----------
+        image_name : str
+            Name of the docker image.
+
+        Returns
+        -------
+        str
+            The hash of the build context of the image, None if the image
+            does not exist or has no such label.
+        """
+        inspect_command = ["docker", "image", "inspect", "--format",
+                           '{{ index .Config.Labels "' + CONTEXT_HASH_LABEL + '" }}', image_name]
+        process = subprocess.run(inspect_command, stdout=subprocess.PIPE, stderr=subprocess.PIPE)
+        if process.returncode != 0:
+            return None
+        return process.stdout.decode().strip() or None
+
+    @docker_running
+    def _check_resource_limits(self) -> bool:
+        """Check whether the docker daemon is able to enforce the configured cpu and memory limits.
+
+        Returns
+        -------
+        Bool
+            Boolean indicating whether all limits can be enforced.
+        """
+        info_command = ["docker", "info", "--format", "{{.NCPU}} {{.MemTotal}} {{.MemoryLimit}}"]
+        process = subprocess.run(info_command, stdout=subprocess.PIPE, stderr=subprocess.PIPE)
+        try:
+            host_cpus, host_memory, memory_limit_supported = process.stdout.decode().split()
+            host_cpus = int(host_cpus)
+            host_memory = int(host_memory) // (1024 * 1024)
+        except ValueError:
+            logger.error('Could not determine the resources available to docker!')
+            return False
+
+        if memory_limit_supported != 'true':
+            logger.error('The docker daemon does not support memory limits, the programs would run unconstrained!')
+            return False
+
+        # Up to jobs many fights run in parallel, each of which may run a container of either role.
+        for role, limits in self.resource_limits().items():
+            if limits['cpus'] * self.jobs > host_cpus:
+                logger.error('The {} is configured to use {} cpus in each of {} parallel fights, but only {} are available!'
+                             .format(role, limits['cpus'], self.jobs, host_cpus))
+                return False
+            if limits['memory'] * self.jobs > host_memory:
+                logger.error('The {} is configured to use {} mb of memory in each of {} parallel fights, '
+                             'but only {} mb are available!'.format(role, limits['memory'], self.jobs, host_memory))
+                return False
+
+        return True
+
+    def resource_limits(self) -> dict:
+        """Return the cpu and memory limits that are enforced for the generators and solvers.
+
+        Returns
+        -------
+        dict
+            A mapping between the roles generator and solver and their number
+            of cpus and memory in mb.
+        """
+        return {
+            'generator': {'cpus': self.cpus_generator, 'memory': self.space_generator},
+            'solver': {'cpus': self.cpus_solver, 'memory': self.space_solver}
+        }
+
     @build_successful
     def all_battle_pairs(self) -> list:
         """Generate and return a list of all team pairings for battles."""
@@ -165,7 +509,7 @@ class Match:
         Parameters
         ----------
         battle_type : str
-            Type of battle that is to be run.
+            Type of battle that is to be run, either built-in or registered by a plugin.
         iterations : int
             Number of Battles between each pair of teams (used for averaging results).
         approximation_instance_size : int
@@ -181,14 +525,14 @@ class Match:
         battle_wrapper = None
         self.approximation_instance_size = approximation_instance_size
 
-        if battle_type == 'iterated':
-            battle_wrapper = self._iterated_battle_wrapper
-        elif battle_type == 'averaged':
-            battle_wrapper = self._averaged_battle_wrapper
+        battle_types = get_battle_types()
+        if battle_type in battle_types:
+            battle_wrapper = battle_types[battle_type](self.config)
         else:
             logger.error('Unrecognized battle_type given: "{}"'.format(battle_type))
             return {'Error': 'Unrecognized battle type'}
 
+        self.battle_type = battle_type
         for pair in self.all_battle_pairs():
             results[pair] = results.get(pair, [])
             pair_results = []
@@ -197,18 +541,187 @@ class Match:
 
                 self.generating_team = pair[0]
                 self.solving_team = pair[1]
-                pair_results.append(battle_wrapper())
+                pair_results.append(battle_wrapper.run(self))
             results[pair] = pair_results
 
         return results
 
+    @build_successful
+    def run_on_instance_sizes(self, instance_sizes: list, iterations=1) -> dict:
+        """Execute fights on fixed instance sizes between all teams instead of battles.
+
+        Meant for quickly testing programs on a few instance sizes of interest.
+
+        Parameters
+        ----------
+        instance_sizes : list
+            The instance sizes of the fights.
+        iterations : int
+            Number of fights on each instance size between each pair of teams.
+
+        Returns
+        -------
+        dict
+            A dictionary containing a list of approximation ratios for each
+            pair of teams, ordered by the given instance sizes, with the tuple
+            (generating team, solving team) as a key.
+        """
+        results = dict()
+        self.battle_type = 'instance_sizes'
+        for pair in self.all_battle_pairs():
+            self.generating_team = pair[0]
+            self.solving_team = pair[1]
+            results[pair] = []
+            for instance_size in instance_sizes:
+                for i in range(iterations):
+                    logger.info('{}  Instance Size {}, Fight {}/{}  {}'
+                                .format('#' * 20, instance_size, i + 1, iterations, '#' * 20))
+                    results[pair].append(self._one_fight(instance_size=instance_size))
+        return results
+
+    @build_successful
+    def run_solver_comparison(self, iterations=5, instance_size=10, generating_teams=None) -> dict:
+        """Run the solvers of all teams on the same generated instances and return their results.
+
+        For each generating team, iterations many instances of the given
+        instance size are generated. Every solver is run on each of these
+        instances, such that all solvers are rated on identical inputs. A
+        solver that crashes or runs into a timeout is rated with an
+        approximation ratio of 0 for the respective instance, without
+        affecting the runs of the other solvers.
+
+        Parameters
+        ----------
+        iterations : int
+            Number of instances that are generated by each generating team.
+        instance_size : int
+            Instance size of the generated instances.
+        generating_teams : list
+            Names of the teams whose generators are used. Defaults to all teams.
+
+        Returns
+        -------
+        dict
+            A dictionary containing a list of approximation ratios for each
+            solving team on the instances of each generating team, with the
+            tuple (generating team, solving team) as a key. Instances for
+            which the generator failed are omitted.
+        """
+        results = dict()
+        self.battle_type = 'comparison'
+
+        if self.problem.interactive:
+            logger.error('Solvers of interactive problems can not be compared on shared instances!')
+            return results
+
+        if generating_teams is None:
+            generating_teams = self.team_names
+
+        for generating_team in generating_teams:
+            self.generating_team = generating_team
+            for solving_team in self.team_names:
+                results[(generating_team, solving_team)] = []
+
+            for i in range(iterations):
+                logger.info('{}  Generating Instance {}/{} of Group {}  {}'
+                            .format('#' * 20, i + 1, iterations, generating_team, '#' * 20))
+                generated_instance = self._run_generator(instance_size)
+                if not generated_instance:
+                    logger.warning('Generator {} failed, skipping this instance for all solvers.'.format(generating_team))
+                    continue
+
+                instance, generator_solution = generated_instance
+                for solving_team in self.team_names:
+                    self.solving_team = solving_team
+                    approx_ratio = self._comparison_fight(instance_size, instance, generator_solution)
+                    results[(generating_team, solving_team)].append(approx_ratio)
+
+        return results
+
+    @fight_replayed
+    @docker_running
+    @fight_recorded
+    def _comparison_fight(self, instance_size: int, instance: any, generator_solution: any) -> float:
+        """Execute the solver of the solving team on an already generated instance.
+
+        Any exception raised while running or rating the solver is caught,
+        such that one faulty solver can not abort the comparison.
+
+        Returns
+        -------
+        float
+            Returns the approximation ratio of the solver, 0 if it failed.
+        """
+        try:
+            approximation_ratio = self._run_solver(instance_size, instance, generator_solution)
+        except Exception as e:
+            logger.warning('Running the solver of group {} failed with the following exception: "{}"'
+                           .format(self.solving_team, e))
+            approximation_ratio = 0.0
+        self._blame_solver_failure(approximation_ratio)
+        return approximation_ratio
+
+    @docker_running
+    @build_successful
+    def run_on_instance(self, encoded_instance: bytes, instance_size: int, solving_team: str,
+                        instance_name='instance') -> float:
+        """Run the solver of a team on a given instance, skipping the generator.
+
+        The given instance is expected in the same format as the output of a
+        generator, i.e. it needs to contain a certificate solution as well.
+        It is checked exactly as if a generator had created it.
+
+        Parameters
+        ----------
+        encoded_instance : bytes
+            The instance and certificate, encoded like the output of a generator.
+        instance_size : int
+            The instance size, expected to be a positive int.
+        solving_team : str
+            Name of the team whose solver is run.
+        instance_name : str
+            Name of the instance that is used in place of the generating team in the logs.
+
+        Returns
+        -------
+        float
+            Returns the approximation ratio of the solver, None if the given
+            instance or certificate is malformed or the problem is interactive.
+        """
+        self.generating_team = instance_name
+        self.solving_team = solving_team
+        self.fight_seed = None
+        self.fight_instance = encoded_instance
+        self.fight_rejection_reason = None
+        self.fight_logs = dict()
+
+        if self.problem.interactive:
+            logger.error('Interactive problems have no instances that a solver could be run on!')
+            return None
+
+        if self.problem.compress_instances:
+            try:
+                encoded_instance = decompress_output(encoded_instance)
+            except (OSError, EOFError) as e:
+                logger.error('The given instance {} could not be decompressed: {}'.format(instance_name, e))
+                return None
+
+        checked_instance = self._check_generator_output(encoded_instance, instance_size)
+        if not checked_instance:
+            logger.error('The given instance {} is malformed, not running the solver!'.format(instance_name))
+            return None
+
+        instance, generator_solution = checked_instance
+        return self._run_solver(instance_size, instance, generator_solution, report_solution=True)
+
     @build_successful
     @team_roles_set
-    def _averaged_battle_wrapper(self) -> list:
+    def averaged_battle(self) -> list:
         """Execute one averaged battle between a generating and a solving team.
 
         Execute several fights between two teams on a fixed instance size
-        and determine the average solution quality.
+        and determine the average solution quality. Used by the averaged
+        battle type, custom battle types may build upon it as well.
 
         Returns
         -------
@@ -218,6 +731,10 @@ class Match:
         approximation_ratios = []
         logger.info('==================== Averaged Battle, Instance Size: {}, Iterations: {} ===================='
                     .format(self.approximation_instance_size, self.aproximation_iterations))
+        if self.jobs > 1:
+            logger.info('Running up to {} fights in parallel.'.format(self.jobs))
+            return self._run_fights_in_parallel([self.approximation_instance_size] * self.aproximation_iterations)
+
         for i in range(self.aproximation_iterations):
             logger.info('=============== Iteration: {}/{} ==============='.format(i + 1, self.aproximation_iterations))
             approx_ratio = self._one_fight(instance_size=self.approximation_instance_size)
@@ -225,22 +742,117 @@ class Match:
 
         return approximation_ratios
 
+    def _run_fights_in_parallel(self, instance_sizes: list) -> list:
+        """Execute independent fights in forked processes, running up to jobs many of them at once.
+
+        The fight listeners are passed the records of the fights in the order
+        of the given instance sizes, regardless of the order in which the
+        fights finish. Every fight is forked with the same state of the seed
+        generator that it would have had in a sequential run, such that the
+        seeds of the generators do not depend on the number of jobs.
+
+        Parameters
+        ----------
+        instance_sizes : list
+            The instance size of each fight.
+
+        Returns
+        -------
+        list
+            Returns the approximation ratios of the fights, in the order of the instance sizes.
+        """
+        context = multiprocessing.get_context('fork')
+        approximation_ratios = [None] * len(instance_sizes)
+        fight_records = [None] * len(instance_sizes)
+        finished = [False] * len(instance_sizes)
+        running = dict()
+        next_fight = 0
+        next_record = 0
+
+        while next_record < len(instance_sizes):
+            while next_fight < len(instance_sizes) and len(running) < self.jobs:
+                if self.fight_index < len(self.resumed_fights):
+                    # Recorded fights precede all others, their records are passed on in order.
+                    approximation_ratios[next_fight] = self._one_fight(instance_size=instance_sizes[next_fight])
+                    finished[next_fight] = True
+                    next_fight += 1
+                    continue
+
+                receiver, sender = context.Pipe(duplex=False)
+                process = context.Process(target=self._parallel_fight, args=(instance_sizes[next_fight], sender))
+                process.start()
+                sender.close()
+
+                # Each fight draws exactly one seed, advance the state as the forked fight does.
+                seed = self.seed_generator.randrange(2**32)
+                running[receiver] = (next_fight, process, seed)
+                self.fight_index += 1
+                next_fight += 1
+
+            for receiver in multiprocessing.connection.wait(list(running)) if running else []:
+                index, process, seed = running.pop(receiver)
+                try:
+                    approximation_ratios[index], fight_records[index], phase_times, error = receiver.recv()
+                    for phase, phase_time in phase_times.items():
+                        self.phase_times[phase] += phase_time
+                except EOFError:
+                    error = 'The process of fight {}/{} terminated unexpectedly!'.format(index + 1, len(instance_sizes))
+                    logger.error(error)
+                    approximation_ratios[index] = 0.0
+                # Every fight that consumed a fight index needs a record, such that
+                # the records of a checkpoint stay aligned with the fight indices.
+                if fight_records[index] is None:
+                    fight_records[index] = self._failure_record(instance_sizes[index], seed,
+                                                                error or 'The fight could not be run.')
+                finished[index] = True
+                receiver.close()
+                process.join()
+
+            while next_record < len(instance_sizes) and finished[next_record]:
+                # The records of replayed fights were already passed on while replaying them
+                if fight_records[next_record]:
+                    for listener in self.fight_listeners:
+                        listener(fight_records[next_record])
+                next_record += 1
+
+        return approximation_ratios
+
+    def _parallel_fight(self, instance_size: int, sender: multiprocessing.connection.Connection) -> None:
+        """Execute a single fight within a forked process and send back its result and record."""
+        fight_records = []
+        self.fight_listeners = [fight_records.append]
+        start_phase_times = dict(self.phase_times)
+        error = None
+        try:
+            approximation_ratio = self._one_fight(instance_size=instance_size)
+        except Exception as e:
+            error = 'The fight failed with the following exception: "{}"'.format(e)
+            logger.error(error)
+            approximation_ratio = 0.0
+        if not fight_records:
+            approximation_ratio = 0.0
+        phase_times = {phase: self.phase_times[phase] - start_phase_times[phase] for phase in self.phase_times}
+        sender.send((approximation_ratio, fight_records[0] if fight_records else None, phase_times, error))
+        sender.close()
+
     @build_successful
     @team_roles_set
-    def _iterated_battle_wrapper(self) -> int:
+    def iterated_battle(self, step_strategy: StepStrategy = None) -> int:
         """Execute one iterative battle between a generating and a solving team.
 
         Incrementally try to search for the highest n for which the solver is
-        still able to solve instances.  The base increment value is multiplied
-        with the square of the iterations since the last unsolvable instance.
-        Only once the solver fails after the multiplier is reset, it counts as
-        failed. Since this would heavily favour probabilistic algorithms (That
-        may have only failed by chance and are able to solve a certain instance
-        size on a second try), we cap the maximum solution size by the first
-        value that an algorithm has failed on.
+        still able to solve instances. The instance sizes are chosen by the
+        given step strategy, see the StepStrategy subclasses of the
+        battle_wrapper module.
+
+        The battle automatically ends and declares the solver as the winner
+        once the iteration cap is reached, which is set in the config.ini. Used
+        by the iterated battle type, custom battle types may build upon it as well.
 
-        The wrapper automatically ends the battle and declares the solver as the
-        winner once the iteration cap is reached, which is set in the config.ini.
+        Parameters
+        ----------
+        step_strategy : StepStrategy
+            The step strategy of the battle, the default one if None.
 
         Returns
         -------
@@ -248,54 +860,84 @@ class Match:
             Returns the biggest instance size for which the solving team still
             found a solution.
         """
-        n = self.problem.n_start
-        maximum_reached_n = 0
-        i = 0
-        n_cap = self.iteration_cap
-        alive = True
-
-        logger.info('==================== Iterative Battle, Instanze Size Cap: {} ===================='.format(n_cap))
-        while alive:
-            logger.info('=============== Instance Size: {}/{} ==============='.format(n, n_cap))
+        if step_strategy is None:
+            step_strategy = DefaultStepStrategy(self.problem.n_start, self.iteration_cap)
+        n = step_strategy.n_start
+        failing_instance_saved = False
+
+        logger.info('==================== Iterative Battle, Instanze Size Cap: {} ===================='
+                    .format(self.iteration_cap))
+        while n is not None:
+            logger.info('=============== Instance Size: {}/{} ==============='.format(n, self.iteration_cap))
             approx_ratio = self._one_fight(instance_size=n)
-            if approx_ratio == 0.0:
-                alive = False
-            elif approx_ratio > self.approximation_ratio:
-                logger.info('Solver {} does not meet the required solution quality at instance size {}. ({}/{})'
-                            .format(self.solving_team, n, approx_ratio, self.approximation_ratio))
-                alive = False
-
-            if not alive and i > 1:
-                # The step size increase was too aggressive, take it back and reset the increment multiplier
-                logger.info('Setting the solution cap to {}...'.format(n))
-                n_cap = n
-                n -= i * i
-                i = 0
-                alive = True
-            elif n > maximum_reached_n and alive:
-                # We solved an instance of bigger size than before
-                maximum_reached_n = n
-
-            if n + 1 == n_cap:
-                alive = False
-                break
-
-            i += 1
-            n += i * i
-
-            if n >= n_cap and n_cap != self.iteration_cap:
-                # We have failed at this value of n already, reset the step size!
-                n -= i * i - 1
-                i = 1
-            elif n >= n_cap and n_cap == self.iteration_cap:
-                logger.info('Solver {} exceeded the instance size cap of {}!'.format(self.solving_team, self.iteration_cap))
-                maximum_reached_n = self.iteration_cap
-                alive = False
-        return maximum_reached_n
+            solved = approx_ratio != 0.0 and approx_ratio <= self.approximation_ratio
 
+            if not solved and not failing_instance_saved:
+                self._save_failing_instance(n)
+                failing_instance_saved = True
+
+            n = step_strategy.next_size(n, solved)
+
+        if step_strategy.maximum_reached_n == self.iteration_cap:
+            logger.info('Solver {} exceeded the instance size cap of {}!'.format(self.solving_team, self.iteration_cap))
+        return step_strategy.maximum_reached_n
+
+    def _save_failing_instance(self, instance_size: int) -> None:
+        """Write the instance of the last fight to the directory of failing instances, if one is set.
+
+        The file contains the instance and the certificate of the generator in
+        the format of the output of the generator. It is referenced together
+        with the reason of the failure in the list failing_instances.
+
+        Parameters
+        ----------
+        instance_size : int
+            The instance size of the last fight.
+        """
+        if not self.failing_instances_directory or self.fight_instance is None:
+            return
+
+        os.makedirs(self.failing_instances_directory, exist_ok=True)
+        instance_path = os.path.join(self.failing_instances_directory, 'fight{}_{}_vs_{}_n{}.txt'
+                                     .format(self.fight_index - 1, self.generating_team, self.solving_team,
+                                             instance_size))
+        with open(instance_path, 'wb') as instance_file:
+            instance_file.write(self.fight_instance)
+
+        self.failing_instances.append({
+            'generating_team': self.generating_team,
+            'solving_team': self.solving_team,
+            'instance_size': instance_size,
+            'failure': self.fight_failure,
+            'path': instance_path
+        })
+        logger.info('Saved the first instance on which solver {} failed ({}) to {}.'
+                    .format(self.solving_team, self.fight_failure, instance_path))
+
+    def run_fight(self, instance_size: int) -> float:
+        """Execute a single fight between the generating and the solving team on the given instance size.
+
+        This is the building block of all battle types. The record of the
+        fight is passed to all fight listeners.
+
+        Parameters
+        ----------
+        instance_size : int
+            The instance size, expected to be a positive int.
+
+        Returns
+        -------
+        float
+            Returns the approximation ratio of the solver against the
+            generator, 0 if the solver failed, None if no fight could be run.
+        """
+        return self._one_fight(instance_size=instance_size)
+
+    @fight_replayed
     @docker_running
     @build_successful
     @team_roles_set
+    @fight_recorded
     def _one_fight(self, instance_size: int) -> float:
         """Execute a single fight of a battle between a given generator and solver for a given instance size.
 
@@ -311,21 +953,187 @@ class Match:
             the generator (1 if optimal, 0 if failed, >=1 if the
             generator solution is optimal).
         """
+        if self.problem.interactive:
+            approximation_ratio = self._run_interaction(instance_size)
+        else:
+            generated_instance = self._run_generator(instance_size)
+            if not generated_instance:
+                self.fight_failed_role = 'generator'
+                return 1.0
+
+            instance, generator_solution = generated_instance
+            approximation_ratio = self._run_solver(instance_size, instance, generator_solution)
+
+        self._blame_solver_failure(approximation_ratio)
+
+        # Iterated battles only count solutions of the required quality as solved
+        if self.battle_type == 'iterated' and approximation_ratio and approximation_ratio > self.approximation_ratio:
+            logger.info('Solver {} does not meet the required solution quality at instance size {}. ({}/{})'
+                        .format(self.solving_team, instance_size, approximation_ratio, self.approximation_ratio))
+            self.fight_failure = 'wrong answer'
+            self.fight_failed_role = 'solver'
+        return approximation_ratio
+
+    def _blame_solver_failure(self, approximation_ratio: float) -> None:
+        """Blame the solver for a fight in which it achieved no score, unless the verification itself failed."""
+        if not approximation_ratio and self.fight_failed_role is None and self.fight_failure != 'verification error':
+            self.fight_failed_role = 'solver'
+
+    def _run_generator(self, instance_size: int) -> tuple:
+        """Run the generator of the generating team and check its instance and certificate.
+
+        Parameters
+        ----------
+        instance_size : int
+            The instance size, expected to be a positive int.
+
+        Returns
+        -------
+        tuple
+            Returns the parsed instance and the parsed certificate of the
+            generator, None if the generator failed.
+        """
         if not isinstance(instance_size, int) or not instance_size > 0:
             logger.error('Expected an instance size to be an int of size at least 1, received: {}'.format(instance_size))
             raise Exception('Expected the instance size to be a positive integer.')
 
-        generator_run_command = self.base_build_command + ["generator-" + str(self.generating_team)]
-        solver_run_command    = self.base_build_command + ["solver-"    + str(self.solving_team)]
+        self.fight_seed = self.seed_generator.randrange(2**32)
+        self.fight_instance = None
 
-        logger.info('Running generator of group {}...\n'.format(self.generating_team))
+        logger.info('Running generator of group {} with seed {}...\n'.format(self.generating_team, self.fight_seed))
 
-        sigh.latest_running_docker_image = "generator-" + str(self.generating_team)
-        encoded_output, _ = run_subprocess(generator_run_command, str(instance_size).encode(),
-                                           self.timeout_generator)
+        self.fight_logs = dict()
+        start_time = timeit.default_timer()
+        encoded_output = self._run_container('generator', "generator-" + str(self.generating_team),
+                                             str(instance_size).encode(), self.timeout_generator,
+                                             ["-e", "ALGOBATTLE_SEED=" + str(self.fight_seed)])
+        self.phase_times['generate'] += timeit.default_timer() - start_time
         if not encoded_output:
-            return 1.0
+            return None
+
+        if self.problem.compress_instances:
+            try:
+                encoded_output = decompress_output(encoded_output)
+            except (OSError, EOFError) as e:
+                self.fight_error = 'The output of the generator could not be decompressed: {}'.format(e)
+                logger.warning(self.fight_error)
+                return None
+
+        generated_instance = self._check_generator_output(encoded_output, instance_size)
+        if generated_instance:
+            self.fight_instance = encoded_output
+        return generated_instance
+
+    def _run_interaction(self, instance_size: int) -> float:
+        """Let the generator and the solver exchange messages and rate the solver on the transcript.
+
+        A generator that exceeds its timeout or terminates before the
+        interaction is finished loses the fight, as does a solver.
 
+        Parameters
+        ----------
+        instance_size : int
+            The instance size, expected to be a positive int.
+
+        Returns
+        -------
+        float
+            Returns the approximation ratio of the solver as rated by the problem.
+        """
+        if not isinstance(instance_size, int) or not instance_size > 0:
+            logger.error('Expected an instance size to be an int of size at least 1, received: {}'.format(instance_size))
+            raise Exception('Expected the instance size to be a positive integer.')
+
+        self.fight_seed = self.seed_generator.randrange(2**32)
+        generator_image = "generator-" + str(self.generating_team)
+        solver_image = "solver-" + str(self.solving_team)
+        container_names = {'generator': 'algobattle-' + uuid.uuid4().hex, 'solver': 'algobattle-' + uuid.uuid4().hex}
+
+        logger.info('Running an interaction between generator {} and solver {} with seed {}...\n'
+                    .format(self.generating_team, self.solving_team, self.fight_seed))
+
+        programs = {
+            'generator': InteractiveProcess(self.base_build_command + self.generator_run_options +
+                                            ["--name", container_names['generator'],
+                                             "-e", "ALGOBATTLE_SEED=" + str(self.fight_seed), generator_image],
+                                            self.timeout_generator),
+            'solver': InteractiveProcess(self.base_build_command + self.solver_run_options +
+                                         ["--name", container_names['solver'], solver_image],
+                                         self.timeout_solver)
+        }
+        teams = {'generator': self.generating_team, 'solver': self.solving_team}
+        transcript = []
+        approximation_ratio = None
+        try:
+            message = str(instance_size).encode()
+            sender, receiver = 'solver', 'generator'
+            for round in range(self.problem.interaction_rounds * 2):
+                if not programs[receiver].send(message):
+                    approximation_ratio = 1.0 if receiver == 'generator' else 0.0
+                    self.fight_failed_role = receiver
+                    if receiver == 'solver':
+                        self.fight_failure = 'crash'
+                    break
+                sender, receiver = receiver, sender
+
+                message = programs[sender].receive()
+                if message is None:
+                    logger.warning('The {} of group {} did not answer in round {}!'
+                                   .format(sender, teams[sender], round // 2))
+                    approximation_ratio = 1.0 if sender == 'generator' else 0.0
+                    self.fight_failed_role = sender
+                    if sender == 'solver':
+                        self.fight_failure = 'timeout' if programs[sender].waiting_time >= self.timeout_solver \
+                            else 'crash'
+                    break
+                transcript.append((sender, message))
+
+                start_time = timeit.default_timer()
+                message, error = run_with_limits(self.problem.interact, (instance_size, round // 2, sender, message),
+                                                 self.timeout_verification, self.space_verification)
+                self.phase_times['check_semantics'] += timeit.default_timer() - start_time
+                if error:
+                    self.fight_error = 'Verification step interact failed: {}'.format(error)
+                    self.fight_failure = 'verification error'
+                    logger.error(self.fight_error)
+                    approximation_ratio = 0.0
+                    break
+                if message is None:
+                    break
+        finally:
+            sigh.latest_running_docker_image = generator_image
+            for role in ['generator', 'solver']:
+                self.fight_logs[role] = programs[role].stop()
+                sigh._kill_spawned_docker_containers(container_names[role])
+
+        if approximation_ratio is not None:
+            return approximation_ratio
+
+        approximation_ratio = self._unpack_score(self._verify('calculate_score', self.problem.rate_interaction,
+                                                              instance_size, transcript))
+        if not approximation_ratio:
+            self.fight_failure = self._verification_failure(approximation_ratio)
+            return 0.0
+        logger.info('Solver {} achieves an approx. ratio of {} in the interaction at instance size {}.'
+                    .format(self.solving_team, approximation_ratio, instance_size))
+        return approximation_ratio
+
+    def _check_generator_output(self, encoded_output: bytes, instance_size: int) -> tuple:
+        """Parse the output of a generator and check its instance and certificate.
+
+        Parameters
+        ----------
+        encoded_output : bytes
+            The raw output of the generator.
+        instance_size : int
+            The instance size, expected to be a positive int.
+
+        Returns
+        -------
+        tuple
+            Returns the parsed instance and the parsed certificate of the
+            generator, None if either of them is invalid.
+        """
         raw_instance_with_solution = self.problem.parser.decode(encoded_output)
 
         logger.info('Checking generated instance and certificate...')
@@ -334,47 +1142,269 @@ class Match:
         instance                   = self.problem.parser.parse_instance(raw_instance, instance_size)
         generator_solution         = self.problem.parser.parse_solution(raw_solution, instance_size)
 
-        if not self.problem.verifier.verify_semantics_of_instance(instance, instance_size):
+        if not self._verify('check_semantics', self.problem.verifier.verify_semantics_of_instance,
+                            instance, instance_size):
             logger.warning('Generator {} created a malformed instance at instance size {}!'
                            .format(self.generating_team, instance_size))
-            return 1.0
+            return None
+
+        # The size is only determined for instances that passed the semantic check, a
+        # problem that still fails to determine it only costs the generator its fight.
+        try:
+            generated_instance_size = self.problem.instance_size(instance)
+        except NotImplementedError:
+            generated_instance_size = None
+        except Exception as e:
+            self.fight_error = 'The size of the generated instance could not be determined: {}'.format(e)
+            logger.warning(self.fight_error)
+            return None
+        if generated_instance_size is not None and generated_instance_size != instance_size:
+            self.fight_error = 'The generator created an instance of size {} instead of the requested size {}.' \
+                .format(generated_instance_size, instance_size)
+            logger.warning('Generator {} created an instance of the wrong size at instance size {}!'
+                           .format(self.generating_team, instance_size))
+            return None
 
-        if not self.problem.verifier.verify_semantics_of_solution(generator_solution, instance_size, True):
+        if not self._verify('check_semantics', self.problem.verifier.verify_semantics_of_solution,
+                            generator_solution, instance_size, True):
             logger.warning('Generator {} created a malformed solution at instance size {}!'
                            .format(self.generating_team, instance_size))
-            return 1.0
+            return None
 
-        if not self.problem.verifier.verify_solution_against_instance(instance, generator_solution, instance_size, True):
+        if not self._verify('check_semantics', self.problem.verifier.verify_solution_against_instance,
+                            instance, generator_solution, instance_size, True):
             logger.warning('Generator {} failed at instance size {} due to a wrong certificate for its generated instance!'
                            .format(self.generating_team, instance_size))
-            return 1.0
+            return None
 
         logger.info('Generated instance and certificate are valid!\n\n')
 
+        return instance, generator_solution
+
+    def _run_solver(self, instance_size: int, instance: any, generator_solution: any, report_solution=False) -> float:
+        """Run the solver of the solving team on a given instance and rate its solution.
+
+        Parameters
+        ----------
+        instance_size : int
+            The instance size, expected to be a positive int.
+        instance : any
+            The parsed instance that is to be solved.
+        generator_solution : any
+            The parsed certificate of the generator for the given instance.
+        report_solution : bool
+            Flag indicating whether to log the parsed solution of the solver.
+
+        Returns
+        -------
+        float
+            Returns the approximation ratio of the solver against
+            the generator (1 if optimal, 0 if failed, >=1 if the
+            generator solution is optimal).
+        """
+        encoded_instance = self.problem.parser.encode(instance)
+        if self.problem.compress_instances:
+            encoded_instance = gzip.compress(encoded_instance)
+
         logger.info('Running solver of group {}...\n'.format(self.solving_team))
 
-        sigh.latest_running_docker_image = "solver-" + str(self.solving_team)
-        encoded_output, _ = run_subprocess(solver_run_command, self.problem.parser.encode(instance),
-                                           self.timeout_solver)
-        if not encoded_output:
+        start_time = timeit.default_timer()
+        encoded_output = self._run_container('solver', "solver-" + str(self.solving_team), encoded_instance,
+                                             self.timeout_solver)
+        self.phase_times['solve'] += timeit.default_timer() - start_time
+
+        # Output of a solver that did not exit cleanly may have been cut off
+        # mid-write, so it is never decoded.
+        partial_output = self.fight_logs['solver'].get('stdout')
+        exit_code = self.fight_logs['solver'].get('exit_code')
+        timed_out = 'exit_code' in self.fight_logs['solver'] and exit_code is None
+        if partial_output and not encoded_output:
+            self.fight_error = 'Incomplete output: the solver ran into the timeout after writing {} bytes.' \
+                .format(len(partial_output))
+            self.fight_failure = 'timeout'
+            logger.warning(self.fight_error)
+            return 0.0
+        elif encoded_output and exit_code:
+            self.fight_error = 'Incomplete output: the solver exited with code {} after writing {} bytes.' \
+                .format(exit_code, len(encoded_output))
+            self.fight_failure = 'crash'
+            logger.warning(self.fight_error)
+            return 0.0
+        elif not encoded_output:
+            self.fight_failure = 'timeout' if timed_out else 'crash'
             return 0.0
 
-        raw_solver_solution = self.problem.parser.decode(encoded_output)
+        try:
+            if self.problem.compress_instances:
+                encoded_output = decompress_output(encoded_output)
+            raw_solver_solution = self.problem.parser.decode(encoded_output)
+        except Exception as e:
+            self.fight_error = 'The output of the solver could not be decoded: {}'.format(e)
+            self.fight_failure = 'wrong answer'
+            logger.warning(self.fight_error)
+            return 0.0
 
         logger.info('Checking validity of the solvers solution...')
 
         solver_solution = self.problem.parser.parse_solution(raw_solver_solution, instance_size)
-        if not self.problem.verifier.verify_semantics_of_solution(solver_solution, instance_size, True):
+        if report_solution:
+            logger.info('Parsed solution of solver {}: {}'.format(self.solving_team, solver_solution))
+        valid = self._verify('check_semantics', self.problem.verifier.verify_semantics_of_solution,
+                             solver_solution, instance_size, True)
+        if not valid:
+            self.fight_failure = self._verification_failure(valid)
             logger.warning('Solver {} created a malformed solution at instance size {}!'
                            .format(self.solving_team, instance_size))
             return 0.0
-        elif not self.problem.verifier.verify_solution_against_instance(instance, solver_solution, instance_size, False):
+        valid = self._verify('check_semantics', self.problem.verifier.verify_solution_against_instance,
+                             instance, solver_solution, instance_size, False)
+        if not valid:
+            self.fight_failure = self._verification_failure(valid)
             logger.warning('Solver {} yields a wrong solution at instance size {}!'
                            .format(self.solving_team, instance_size))
             return 0.0
         else:
-            approximation_ratio = self.problem.verifier.calculate_approximation_ratio(instance, instance_size,
-                                                                                      generator_solution, solver_solution)
+            approximation_ratio = self._unpack_score(
+                self._verify('calculate_score', self.problem.verifier.calculate_approximation_ratio,
+                             instance, instance_size, generator_solution, solver_solution))
+            if approximation_ratio is None:
+                self.fight_failure = 'verification error'
+                return 0.0
             logger.info('Solver {} yields a valid solution with an approx. ratio of {} at instance size {}.'
                         .format(self.solving_team, approximation_ratio, instance_size))
             return approximation_ratio
+
+    def _verification_failure(self, result: any) -> str:
+        """Return the failure label of a fight whose solution did not pass a verification step.
+
+        Parameters
+        ----------
+        result : any
+            The falsy return value of _verify.
+
+        Returns
+        -------
+        str
+            'verification error' if the verification itself failed, 'wrong answer' otherwise.
+        """
+        return 'verification error' if result is None else 'wrong answer'
+
+    def _unpack_score(self, score: any) -> float:
+        """Return the approximation ratio of a score and keep its components for the record of the fight.
+
+        Parameters
+        ----------
+        score : float or Score
+            The score as returned by the problem.
+
+        Returns
+        -------
+        float
+            The approximation ratio.
+        """
+        if not isinstance(score, Score):
+            return score
+
+        self.fight_score_components = dict(score.components)
+        if score.components:
+            logger.info('Components of the score: {}'.format(
+                ', '.join('{}: {}'.format(name, value) for name, value in score.components.items())))
+        return score.score
+
+    def _run_container(self, role: str, image: str, input: bytes, timeout: float, options: list = None) -> bytes:
+        """Run a docker container of a program and capture its output in the logs of the current fight.
+
+        If docker itself fails to start the container, it is restarted up to
+        docker_retries times with an exponential backoff. Failures of the
+        program, like a nonzero exit code or a timeout, are never retried.
+
+        Parameters
+        ----------
+        role : str
+            The role of the program, either 'generator' or 'solver'.
+        image : str
+            The name of the docker image.
+        input : bytes
+            The input that is passed to the program via stdin.
+        timeout : float
+            The timeout for the program in seconds.
+        options : list
+            Additional options for docker run.
+
+        Returns
+        -------
+        bytes
+            The output of the program, None if it ran into the timeout.
+        """
+        run_options = self.generator_run_options if role == 'generator' else self.solver_run_options
+        sigh.latest_running_docker_image = image
+
+        for attempt in range(self.docker_retries + 1):
+            # Every attempt needs a fresh name, as a failed container may still block its name.
+            container_name = 'algobattle-' + uuid.uuid4().hex
+            run_command = self.base_build_command + run_options + self._compression_options() + (options or []) + \
+                ["--name", container_name, image]
+
+            self.fight_logs[role] = dict()
+            encoded_output, _ = run_subprocess(run_command, input, timeout, output_log=self.fight_logs[role],
+                                               container_name=container_name)
+            if not is_docker_daemon_error(self.fight_logs[role]):
+                return encoded_output
+
+            error = truncate_output(self.fight_logs[role]['stderr'], self.output_capture_limit).strip()
+            if attempt < self.docker_retries:
+                delay = self.docker_retry_delay * 2 ** attempt
+                logger.warning('Docker failed to run the {} ({}), retrying in {} seconds...'.format(role, error, delay))
+                time.sleep(delay)
+
+        self.fight_error = 'Docker failed to run the {} {} times: {}'.format(role, self.docker_retries + 1, error)
+        logger.error(self.fight_error)
+        return None
+
+    def _compression_options(self) -> list:
+        """Return the options of docker run that announce the compression of instances to the programs."""
+        if self.problem.compress_instances:
+            return ["-e", "ALGOBATTLE_COMPRESSION=gzip"]
+        return []
+
+    def _verify(self, phase: str, verification: Callable, *args) -> any:
+        """Execute a method of the verifier of the problem within the configured time and memory limits.
+
+        Parameters
+        ----------
+        phase : str
+            The phase of the match that the time of the method is accounted
+            to, either check_semantics or calculate_score.
+        verification : Callable
+            The method of the verifier that is to be executed.
+        *args
+            The arguments that are passed to the method.
+
+        Returns
+        -------
+        any
+            The return value of the method, False if it raised a VerificationError
+            and None if it exceeded its limits or raised any other exception.
+        """
+        name = getattr(verification, '__name__', repr(verification))
+
+        def verification_with_reason(*args):
+            try:
+                return verification(*args), None
+            except VerificationError as e:
+                return False, str(e)
+
+        start_time = timeit.default_timer()
+        result, error = run_with_limits(verification_with_reason, args, self.timeout_verification,
+                                        self.space_verification)
+        self.phase_times[phase] += timeit.default_timer() - start_time
+        if error:
+            self.fight_error = 'Verification step {} failed: {}'.format(name, error)
+            logger.error(self.fight_error)
+            return None
+
+        result, reason = result
+        if result is False:
+            self.fight_rejection_reason = '{}: {}'.format(name, reason or 'the check returned False')
+            logger.warning('Verification step {} rejected: {}'.format(name, reason or 'no reason given'))
+        return result
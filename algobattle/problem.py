@@ -1,4 +1,14 @@
 from abc import ABCMeta, abstractmethod
+import importlib.util
+import os
+import sys
+
+from algobattle.parser import Parser
+from algobattle.verifier import Verifier
+
+
+class ProblemLoadError(ValueError):
+    """Raised if a problem could not be loaded, listing everything that is wrong with its module."""
 
 
 class Problem(metaclass=ABCMeta):
@@ -7,8 +17,27 @@ class Problem(metaclass=ABCMeta):
     Enforces the necessary attribute n_start which is the smallest iteration
     size for a problem as well as a flag indicating whether a problem is
     usable in an approximation setting.
+
+    Problems whose flag interactive is set are not solved by a single
+    exchange of an instance and a solution. Instead, the generator and the
+    solver stay alive and take turns in sending messages, starting with the
+    generator, for at most interaction_rounds rounds. Every message is passed
+    through the interact method before being forwarded to the other program,
+    and the whole exchange is rated by the rate_interaction method.
+
+    Problems that implement the method instance_size reject every
+    generated instance whose size differs from the requested instance size.
+
+    Problems with large instances may set the flag compress_instances. The
+    generator and the solver are then started with the environment variable
+    ALGOBATTLE_COMPRESSION=gzip, the solver receives the instance
+    gzip-compressed on stdin and both programs may gzip their output.
     """
 
+    interactive = False
+    interaction_rounds = 100
+    compress_instances = False
+
     @property
     @abstractmethod
     def name(self):
@@ -34,5 +63,126 @@ class Problem(metaclass=ABCMeta):
     def approximable(self):
         raise NotImplementedError
 
+    def interact(self, instance_size: int, round: int, sender: str, message: bytes) -> bytes:
+        """Mediate a single message of an interaction between the generator and the solver.
+
+        Parameters
+        ----------
+        instance_size : int
+            The instance size of the fight.
+        round : int
+            The current round, starting at 0.
+        sender : str
+            The program that sent the message, either 'generator' or 'solver'.
+        message : bytes
+            The message, without its trailing newline.
+
+        Each call is subject to the time and memory limits of the verifier.
+
+        Returns
+        -------
+        bytes
+            The message that is forwarded to the other program, None if the
+            interaction is to be ended.
+        """
+        return message
+
+    def rate_interaction(self, instance_size: int, transcript: list) -> float:
+        """Rate the performance of the solver in an interaction.
+
+        Parameters
+        ----------
+        instance_size : int
+            The instance size of the fight.
+        transcript : list
+            List of all exchanged messages as tuples of the sender and the message.
+
+        Returns
+        -------
+        float or Score
+            The approximation ratio of the solver (1 if optimal, 0 if failed),
+            optionally wrapped in a Score object together with named sub-scores.
+        """
+        raise NotImplementedError
+
+    def instance_size(self, instance: any) -> int:
+        """Determine the size of a parsed instance.
+
+        By default, the size of generated instances is not checked.
+
+        Parameters
+        ----------
+        instance : any
+            The syntactically checked instance.
+
+        Returns
+        -------
+        int
+            The size of the instance.
+        """
+        raise NotImplementedError
+
     def __str__(self) -> str:
         return self.name
+
+    @staticmethod
+    def from_file(problem_path: str) -> 'Problem':
+        """Import a problem module and return an object of the problem that it defines.
+
+        The module is expected to export a subclass of Problem named Problem
+        that implements all of its abstract attributes, with a parser that is
+        a Parser and a verifier that is a Verifier.
+
+        Parameters
+        ----------
+        problem_path : str
+            Path to the problem directory or to its __init__.py file.
+
+        Returns
+        -------
+        Problem
+            An object of the problem.
+
+        Raises
+        ------
+        ProblemLoadError
+            If the module could not be imported or does not define a valid problem.
+        """
+        module_path = os.path.join(problem_path, '__init__.py') if os.path.isdir(problem_path) else problem_path
+        if not os.path.isfile(module_path):
+            raise ProblemLoadError('The problem module "{}" does not exist.'.format(module_path))
+
+        try:
+            spec = importlib.util.spec_from_file_location("problem", module_path)
+            module = importlib.util.module_from_spec(spec)
+            sys.modules[spec.name] = module
+            spec.loader.exec_module(module)
+        except Exception as e:
+            raise ProblemLoadError('Importing the problem module "{}" failed with the following exception: "{}"'
+                                   .format(module_path, e))
+
+        problem_class = getattr(module, 'Problem', None)
+        if problem_class is None:
+            raise ProblemLoadError('The problem module "{}" does not export a class named Problem.'.format(module_path))
+        if not isinstance(problem_class, type) or not issubclass(problem_class, Problem):
+            raise ProblemLoadError('The class Problem of the problem module "{}" is not a subclass of '
+                                   'algobattle.problem.Problem.'.format(module_path))
+
+        issues = []
+        for attribute in sorted(getattr(problem_class, '__abstractmethods__', [])):
+            issues.append('the attribute {} is not defined'.format(attribute))
+        if 'parser' not in problem_class.__abstractmethods__ and not isinstance(problem_class.parser, Parser) \
+                and not problem_class.interactive:
+            issues.append('the parser is not an object of a subclass of algobattle.parser.Parser')
+        if 'verifier' not in problem_class.__abstractmethods__ and not isinstance(problem_class.verifier, Verifier) \
+                and not problem_class.interactive:
+            issues.append('the verifier is not an object of a subclass of algobattle.verifier.Verifier')
+        if issues:
+            raise ProblemLoadError('The problem {} of the module "{}" is invalid: {}.'
+                                   .format(problem_class.__name__, module_path, '; '.join(issues)))
+
+        try:
+            return problem_class()
+        except Exception as e:
+            raise ProblemLoadError('Initializing the problem of the module "{}" failed with the following exception: "{}"'
+                                   .format(module_path, e))
@@ -1,18 +1,69 @@
-"""Generate a list of random numbers, with 4 random numbers manually set to be a valid solution."""
+"""Generate a list of numbers, with 4 numbers manually set to be a valid solution.
+
+The numbers are chosen according to the strategy given by the option --strategy:
+random: Uniformly random numbers (default).
+adversarial: Numbers whose pairwise sums lie close together but are all
+    distinct, except for the sums of the two pairs of the planted solution.
+    Solvers thus need to consider as many pairs as possible.
+"""
+import argparse
 import random
 
-fin = open("input")
-fout = open("output", "w")
-n = int(fin.readline())
-
-randlist = [random.randint(0, 2**62) for i in range(n)]
-sol = random.sample(range(n), 4)
-randlist[sol[0]] = random.randint(2**60, 2**62)
-randlist[sol[1]] = random.randint(2**60, 2**62)
-randlist[sol[2]] = random.randint(0, 2**59)
-randlist[sol[3]] = randlist[sol[0]] + randlist[sol[1]] - randlist[sol[2]]
-
-fout.write(" ".join(str(i) for i in randlist))
-fout.write("\n")
-fout.write(" ".join(str(i) for i in sol))
-fout.close()
+
+def random_instance(n):
+    randlist = [random.randint(0, 2**62) for i in range(n)]
+    sol = random.sample(range(n), 4)
+    randlist[sol[0]] = random.randint(2**60, 2**62)
+    randlist[sol[1]] = random.randint(2**60, 2**62)
+    randlist[sol[2]] = random.randint(0, 2**59)
+    randlist[sol[3]] = randlist[sol[0]] + randlist[sol[1]] - randlist[sol[2]]
+    return randlist, sol
+
+
+def is_prime(k):
+    if k < 2:
+        return False
+    return all(k % d != 0 for d in range(2, int(k**0.5) + 1))
+
+
+def adversarial_instance(n):
+    p = n
+    while not is_prime(p):
+        p += 1
+
+    # The numbers 2pk + (k^2 mod p) for distinct k < p form a Sidon set,
+    # i.e. all sums of two of them are distinct. They are smaller than 2p^2.
+    sidon = [2 * p * k + (k * k) % p for k in random.sample(range(p), n - 1)]
+    s_a, s_b, s_c = sidon[:3]
+
+    # Shifting the planted numbers by multiples of m separates their sums from
+    # all other sums, apart from the planted solution a + b = c + d.
+    m = 8 * p * p
+    numbers = sidon[3:] + [m + s_a, m + s_b, s_c, 2 * m + s_a + s_b - s_c]
+
+    positions = list(range(n))
+    random.shuffle(positions)
+    instance = [0] * n
+    for position, number in zip(positions, numbers):
+        instance[position] = number
+    return instance, positions[-4:]
+
+
+STRATEGIES = {'random': random_instance, 'adversarial': adversarial_instance}
+
+
+if __name__ == '__main__':
+    argument_parser = argparse.ArgumentParser()
+    argument_parser.add_argument('--strategy', choices=sorted(STRATEGIES), default='random')
+    strategy = argument_parser.parse_args().strategy
+
+    fin = open("input")
+    fout = open("output", "w")
+    n = int(fin.readline())
+
+    randlist, sol = STRATEGIES[strategy](n)
+
+    fout.write(" ".join(str(i) for i in randlist))
+    fout.write("\n")
+    fout.write(" ".join(str(i) for i in sol))
+    fout.close()
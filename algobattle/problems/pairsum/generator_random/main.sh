@@ -1,5 +1,5 @@
 #!/usr/bin/env sh
 cat > input
-python main.py 1>&2
+python main.py --strategy "${STRATEGY:-random}" 1>&2
 cat output
 
@@ -15,3 +15,6 @@ class Pairsum(Problem):
     parser = PairsumParser()
     verifier = PairsumVerifier()
     approximable = False
+
+    def instance_size(self, instance):
+        return len(instance)
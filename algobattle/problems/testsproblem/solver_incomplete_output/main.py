@@ -0,0 +1,6 @@
+fin = open("input")
+line = fin.readline()
+
+fout = open("output", "w")
+fout.write("s 1 ")
+fout.close()
@@ -0,0 +1,5 @@
+#!/usr/bin/env sh
+cat > input
+python main.py 1>&2
+cat output
+exit 1
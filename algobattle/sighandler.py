@@ -18,9 +18,15 @@ def signal_handler(sig, frame):
     sys.exit(0)
 
 
-def _kill_spawned_docker_containers():
-    """Terminate all running docker containers spawned by this program."""
-    if latest_running_docker_image:
+def _kill_spawned_docker_containers(container_name=None):
+    """Terminate all running docker containers spawned by this program.
+
+    If a container name is given, only the container of this name is
+    terminated, leaving other containers of the same image running.
+    """
+    if container_name:
+        subprocess.run(['docker', 'kill', container_name], stdout=subprocess.DEVNULL, stderr=subprocess.DEVNULL)
+    elif latest_running_docker_image:
         subprocess.run('docker ps -a -q --filter ancestor={} | xargs -r docker kill > /dev/null 2>&1'
                        .format(latest_running_docker_image), shell=True)
 
@@ -3,25 +3,142 @@ import os
 import logging
 import timeit
 import subprocess
-import importlib.util
-import sys
+import multiprocessing
+import multiprocessing.connection
+import resource
+import hashlib
+import gzip
+import threading
+import configparser
+from typing import Callable
 
 import algobattle
 import algobattle.problems.delaytest as DelaytestProblem
 import algobattle.sighandler as sigh
-from algobattle.problem import Problem
+from algobattle.problem import Problem, ProblemLoadError
+from algobattle.battle_wrapper import get_battle_types
 
 
 logger = logging.getLogger('algobattle.util')
 
 
+class ConfigError(ValueError):
+    """Raised if a config file could not be parsed, naming the offending key if there is one."""
+
+
+def load_config(config_path: str) -> configparser.ConfigParser:
+    """Read a config file in the .ini, .toml or .yaml/.yml format, depending on its file extension.
+
+    TOML and YAML files need to contain a table of sections, e.g.
+    run_parameters, each of which contains a table of options with scalar
+    values. Like ConfigParser.read, a file that does not exist is ignored.
+
+    Parameters
+    ----------
+    config_path : str
+        Path to the config file.
+
+    Returns
+    -------
+    configparser.ConfigParser
+        The options of the config file.
+
+    Raises
+    ------
+    ConfigError
+        If the file could not be parsed or contains an invalid option.
+    """
+    config = configparser.ConfigParser()
+    extension = os.path.splitext(config_path)[1].lower()
+    if extension not in ['.toml', '.yaml', '.yml']:
+        try:
+            config.read(config_path)
+        except configparser.Error as e:
+            raise ConfigError('The config file "{}" could not be parsed: {}'.format(config_path, e))
+        return config
+
+    if not os.path.isfile(config_path):
+        return config
+
+    try:
+        if extension == '.toml':
+            try:
+                import tomllib
+            except ImportError:
+                import toml as tomllib
+            with open(config_path, 'rb' if tomllib.__name__ == 'tomllib' else 'r') as config_file:
+                options = tomllib.load(config_file)
+        else:
+            import yaml
+            with open(config_path) as config_file:
+                options = yaml.safe_load(config_file) or dict()
+    except ImportError:
+        raise ConfigError('Reading the config file "{}" requires the package {}.'
+                          .format(config_path, 'toml' if extension == '.toml' else 'PyYAML'))
+    except Exception as e:
+        raise ConfigError('The config file "{}" could not be parsed: {}'.format(config_path, e))
+
+    if not isinstance(options, dict):
+        raise ConfigError('The config file "{}" does not contain a table of sections.'.format(config_path))
+    for section, section_options in options.items():
+        if not isinstance(section_options, dict):
+            raise ConfigError('The key "{}" of the config file "{}" is not a table of options.'
+                              .format(section, config_path))
+        for key, value in section_options.items():
+            if isinstance(value, bool):
+                section_options[key] = str(value).lower()
+            elif not isinstance(value, (str, int, float)):
+                raise ConfigError('The key "{}.{}" of the config file "{}" does not have a scalar value.'
+                                  .format(section, key, config_path))
+    config.read_dict(options)
+    return config
+
+
+_REQUIRED = object()
+
+
+def read_run_parameter(config: configparser.ConfigParser, key: str, option_type: type = int, fallback=_REQUIRED):
+    """Read an option of the run parameters of a config, converted to the given type.
+
+    Parameters
+    ----------
+    config : configparser.ConfigParser
+        The config, e.g. as returned by load_config.
+    key : str
+        Name of the option in the section run_parameters.
+    option_type : type
+        Type that the value is converted to, int or float.
+    fallback : any
+        Value that is returned if the option is not set. If not given, the option is required.
+
+    Returns
+    -------
+    any
+        The converted value of the option.
+
+    Raises
+    ------
+    ConfigError
+        If a required option is missing or the value can not be converted.
+    """
+    if not config.has_section('run_parameters') or key not in config['run_parameters']:
+        if fallback is _REQUIRED:
+            raise ConfigError('The config file is missing the option "run_parameters.{}".'.format(key))
+        return fallback
+    try:
+        return option_type(config['run_parameters'][key])
+    except ValueError:
+        raise ConfigError('The option "run_parameters.{}" of the config file is not {}.'
+                          .format(key, 'an integer' if option_type is int else 'a number'))
+
+
 def import_problem_from_path(problem_path: str) -> Problem:
     """Try to import and initialize a Problem object from a given path.
 
     Parameters
     ----------
     problem_path : str
-        dict containing the results of match.run().
+        Path to the problem directory.
 
     Returns
     -------
@@ -29,14 +146,9 @@ def import_problem_from_path(problem_path: str) -> Problem:
         Returns an object of the problem if successful, None otherwise.
     """
     try:
-        spec = importlib.util.spec_from_file_location("problem", problem_path + "/__init__.py")
-        Problem = importlib.util.module_from_spec(spec)
-        sys.modules[spec.name] = Problem
-        spec.loader.exec_module(Problem)
-
-        return Problem.Problem()
-    except Exception as e:
-        logger.critical('Importing the given problem failed with the following exception: "{}"'.format(e))
+        return Problem.from_file(problem_path)
+    except ProblemLoadError as e:
+        logger.critical(e)
         return None
 
 
@@ -62,7 +174,7 @@ def measure_runtime_overhead() -> float:
     overheads = []
     for i in range(10):
         sigh.latest_running_docker_image = "generator0"
-        _, timeout = run_subprocess(match.base_build_command + ["generator0"],
+        _, timeout = run_subprocess(match.base_build_command + match.generator_run_options + ["generator0"],
                                     input=str(50 * i).encode(), timeout=match.timeout_generator)
         overheads.append(float(timeout))
 
@@ -72,7 +184,7 @@ def measure_runtime_overhead() -> float:
 
 
 def calculate_points(results: dict, achievable_points: int, team_names: list,
-                     battle_iterations: int, battle_type: str) -> dict:
+                     battle_iterations: int, battle_type: str, config: configparser.ConfigParser = None) -> dict:
     """Calculate the number of achieved points, given results.
 
     Parameters
@@ -87,6 +199,8 @@ def calculate_points(results: dict, achievable_points: int, team_names: list,
         Number of iterations that were made in the match.
     batte_type : str
         Type of battle that was held.
+    config : configparser.ConfigParser
+        The configuration of the match, used to set up the battle type.
 
     Returns
     -------
@@ -98,6 +212,12 @@ def calculate_points(results: dict, achievable_points: int, team_names: list,
     if len(team_names) == 1:
         return {team_names[0]: achievable_points}
 
+    battle_types = get_battle_types()
+    if battle_type not in battle_types:
+        logger.info('Unclear how to calculate points for this type of battle.')
+        return points
+    battle_wrapper = battle_types[battle_type](config)
+
     # We want all groups to be able to achieve the same number of total points, regardless of the number of teams
     normalizer = len(team_names) - 1
     points_per_iteration = round(achievable_points / battle_iterations, 1)
@@ -109,19 +229,8 @@ def calculate_points(results: dict, achievable_points: int, team_names: list,
             for k in range(battle_iterations):
                 results0 = results[(team_names[i], team_names[j])][k]
                 results1 = results[(team_names[j], team_names[i])][k]
-                if battle_type == 'iterated':
-                    valuation0 = results0
-                    valuation1 = results1
-                elif battle_type == 'averaged':
-                    # The valuation of an averaged battle
-                    # is the number of successfully executed battles divided by
-                    # the average competitive ratio of successful battles,
-                    # to account for failures on execution. A higher number
-                    # thus means a better overall result. Normalized to the number of configured points.
-                    valuation0 = (len(results0) / (sum(results0) / len(results0)))
-                    valuation1 = (len(results1) / (sum(results1) / len(results1)))
-                else:
-                    logger.info('Unclear how to calculate points for this type of battle.')
+                valuation0 = battle_wrapper.valuation(results0)
+                valuation1 = battle_wrapper.valuation(results1)
 
                 if valuation0 + valuation1 > 0:
                     points_proportion0 = (valuation0 / (valuation0 + valuation1))
@@ -135,7 +244,8 @@ def calculate_points(results: dict, achievable_points: int, team_names: list,
     return points
 
 
-def run_subprocess(run_command: list, input: bytes, timeout: float, suppress_output=False):
+def run_subprocess(run_command: list, input: bytes, timeout: float, suppress_output=False, output_log: dict = None,
+                   container_name: str = None):
     """Run a given command as a subprocess.
 
     Parameters
@@ -148,6 +258,17 @@ def run_subprocess(run_command: list, input: bytes, timeout: float, suppress_out
         The timeout for the subprocess in seconds.
     suppress_output : bool
         Indicate whether to suppress output to stderr.
+    output_log : dict
+        If given, the raw stdout and stderr of the subprocess are stored in it
+        under the keys 'stdout' and 'stderr', including the partial output of
+        a subprocess that ran into the timeout. The exit code of the
+        subprocess is stored under the key 'exit_code', None on a timeout.
+        The wall-clock time and, if a container name is given and the cgroup
+        of the container is readable, the cpu time of the subprocess are
+        stored in seconds under the keys 'wall_time' and 'cpu_time'.
+    container_name : str
+        Name of the docker container that the command starts. If given, only
+        this container is terminated once the subprocess is finished.
 
     Returns
     -------
@@ -163,21 +284,329 @@ def run_subprocess(run_command: list, input: bytes, timeout: float, suppress_out
     if suppress_output:
         stderr = None
 
+    cpu_time_monitor = None
+    if output_log is not None and container_name:
+        cpu_time_monitor = CpuTimeMonitor(container_name)
+        cpu_time_monitor.start()
+
     with subprocess.Popen(run_command, stdin=subprocess.PIPE, stdout=subprocess.PIPE, stderr=stderr) as p:
         try:
-            raw_output, _ = p.communicate(input=input, timeout=timeout)
-        except subprocess.TimeoutExpired:
+            raw_output, raw_error = p.communicate(input=input, timeout=timeout)
+            if output_log is not None:
+                output_log.update({'stdout': raw_output, 'stderr': raw_error, 'exit_code': p.returncode})
+        except subprocess.TimeoutExpired as e:
             logger.warning('Time limit exceeded!')
+            if output_log is not None:
+                output_log.update({'stdout': e.stdout, 'stderr': e.stderr, 'exit_code': None})
             return None, None
         except Exception as e:
             logger.warning('An exception was thrown while running the subprocess:\n{}'.format(e))
             return None, None
         finally:
+            if output_log is not None:
+                output_log['wall_time'] = round(timeit.default_timer() - start_time, 2)
+                output_log['cpu_time'] = cpu_time_monitor.stop() if cpu_time_monitor else None
             p.kill()
             p.wait()
-            sigh._kill_spawned_docker_containers()
+            sigh._kill_spawned_docker_containers(container_name)
 
     elapsed_time = round(timeit.default_timer() - start_time, 2)
     logger.debug('Approximate elapsed runtime: {}/{} seconds.'.format(elapsed_time, timeout))
 
     return raw_output, elapsed_time
+
+
+class CpuTimeMonitor(threading.Thread):
+    """CpuTimeMonitor class, repeatedly samples the cpu time of a running docker container.
+
+    The cpu time is read from the cgroup of the container, which is removed
+    as soon as the container terminates. The last sample is thus slightly
+    lower than the total cpu time of the container, by at most the cpu time
+    of one sampling interval.
+    """
+
+    def __init__(self, container_name: str, interval: float = 0.05, cgroup_root: str = '/sys/fs/cgroup') -> None:
+        super().__init__(daemon=True)
+        self.container_name = container_name
+        self.interval = interval
+        self.cgroup_root = cgroup_root
+        self.cpu_time = None
+        self._stopped = threading.Event()
+
+    def run(self) -> None:
+        container_id = None
+        while not self._stopped.wait(self.interval):
+            if not container_id:
+                inspect_command = ['docker', 'inspect', '--format', '{{.Id}}', self.container_name]
+                process = subprocess.run(inspect_command, stdout=subprocess.PIPE, stderr=subprocess.DEVNULL)
+                container_id = process.stdout.decode().strip()
+                continue
+            cpu_time = read_cgroup_cpu_time(container_id, self.cgroup_root)
+            if cpu_time is not None:
+                self.cpu_time = cpu_time
+
+    def stop(self) -> float:
+        """Stop sampling and return the last sampled cpu time in seconds, None if it was not readable."""
+        self._stopped.set()
+        self.join()
+        return self.cpu_time
+
+
+def read_cgroup_cpu_time(container_id: str, cgroup_root: str = '/sys/fs/cgroup') -> float:
+    """Read the cpu time that a running docker container used so far from its cgroup.
+
+    Both cgroup v2, with the systemd or the cgroupfs driver, and cgroup v1 are supported.
+
+    Parameters
+    ----------
+    container_id : str
+        The full id of the container.
+    cgroup_root : str
+        The mount point of the cgroup file system.
+
+    Returns
+    -------
+    float
+        The cpu time in seconds, None if the cgroup of the container could not be read.
+    """
+    for cgroup in [os.path.join(cgroup_root, 'system.slice', 'docker-{}.scope'.format(container_id)),
+                   os.path.join(cgroup_root, 'docker', container_id)]:
+        try:
+            with open(os.path.join(cgroup, 'cpu.stat')) as cpu_stat:
+                for line in cpu_stat:
+                    key, value = line.split()
+                    if key == 'usage_usec':
+                        return int(value) / 10**6
+        except (OSError, ValueError):
+            pass
+
+    for cgroup in [os.path.join(cgroup_root, 'cpuacct', 'docker', container_id),
+                   os.path.join(cgroup_root, 'cpu,cpuacct', 'docker', container_id)]:
+        try:
+            with open(os.path.join(cgroup, 'cpuacct.usage')) as cpuacct_usage:
+                return int(cpuacct_usage.read()) / 10**9
+        except (OSError, ValueError):
+            pass
+
+    return None
+
+
+def _virtual_memory_size() -> int:
+    """Return the size of the virtual address space of the current process in bytes, 0 if it can not be read."""
+    try:
+        with open('/proc/self/status') as status:
+            for line in status:
+                if line.startswith('VmSize:'):
+                    return int(line.split()[1]) * 1024
+    except (OSError, ValueError, IndexError):
+        pass
+    return 0
+
+
+def run_with_limits(function: Callable, args: tuple, timeout: float, space: int):
+    """Run a function in a separate process whose runtime and memory are bounded.
+
+    Parameters
+    ----------
+    function : Callable
+        The function that is to be executed.
+    args : tuple
+        The arguments that are passed to the function.
+    timeout : float
+        The timeout for the function in seconds. No timeout is set if 0 or None.
+    space : int
+        The memory cap for the function in mb, on top of the memory that is
+        already in use by the calling process. No cap is set if 0 or None.
+
+    Returns
+    -------
+    any
+        The return value of the function, None if it failed.
+    str
+        A description of why the function failed, None if it was successful.
+    """
+    context = multiprocessing.get_context('fork')
+    receiver, sender = context.Pipe(duplex=False)
+
+    def target():
+        if space:
+            # The forked process already holds the address space of its parent, the cap applies on top of it.
+            _, hard_limit = resource.getrlimit(resource.RLIMIT_AS)
+            soft_limit = _virtual_memory_size() + space * 1024 * 1024
+            if hard_limit != resource.RLIM_INFINITY:
+                soft_limit = min(soft_limit, hard_limit)
+            resource.setrlimit(resource.RLIMIT_AS, (soft_limit, hard_limit))
+        try:
+            sender.send((function(*args), None))
+        except MemoryError:
+            sender.send((None, 'Exceeded the memory limit of {} mb!'.format(space)))
+        except Exception as e:
+            sender.send((None, 'Raised the following exception: "{}"'.format(e)))
+
+    process = context.Process(target=target)
+    process.start()
+    ready = multiprocessing.connection.wait([receiver, process.sentinel], timeout=timeout or None)
+
+    if receiver.poll():
+        result, error = receiver.recv()
+    elif not ready:
+        result, error = None, 'Exceeded the time limit of {} seconds!'.format(timeout)
+    else:
+        process.join()
+        result, error = None, 'Terminated unexpectedly with exit code {}!'.format(process.exitcode)
+
+    process.kill()
+    process.join()
+    receiver.close()
+    sender.close()
+
+    return result, error
+
+
+def hash_build_context(path: str) -> str:
+    """Calculate a hash over all files of a docker build context, including the Dockerfile.
+
+    Parameters
+    ----------
+    path : str
+        Path to the build context.
+
+    Returns
+    -------
+    str
+        The hex digest of the hash, changing whenever a file of the build
+        context is added, removed, renamed or modified.
+    """
+    context_hash = hashlib.sha256()
+    for root, dirs, files in os.walk(path):
+        dirs.sort()
+        for file_name in sorted(files):
+            file_path = os.path.join(root, file_name)
+            context_hash.update(os.path.relpath(file_path, path).encode())
+            context_hash.update(b'\0')
+            with open(file_path, 'rb') as file:
+                context_hash.update(hashlib.sha256(file.read()).digest())
+    return context_hash.hexdigest()
+
+
+def read_base_image(path: str) -> str:
+    """Read the base image of the final stage of the Dockerfile of a docker build context.
+
+    Parameters
+    ----------
+    path : str
+        Path to the build context.
+
+    Returns
+    -------
+    str
+        The image named in the last FROM instruction, None if the build
+        context contains no readable Dockerfile with a FROM instruction.
+    """
+    base_image = None
+    stages = dict()
+    try:
+        with open(os.path.join(path, 'Dockerfile'), 'r') as dockerfile:
+            lines = dockerfile.read().replace('\\\n', ' ').splitlines()
+    except OSError:
+        return None
+
+    for line in lines:
+        words = [word for word in line.split() if not word.startswith('--')]
+        if len(words) < 2 or words[0].upper() != 'FROM':
+            continue
+        # Stages that are built upon an earlier stage inherit its base image
+        base_image = stages.get(words[1].lower(), words[1])
+        if len(words) >= 4 and words[2].upper() == 'AS':
+            stages[words[3].lower()] = base_image
+    return base_image
+
+
+def is_docker_daemon_error(output_log: dict) -> bool:
+    """Check whether a docker run command failed because of docker itself rather than the program it ran.
+
+    Parameters
+    ----------
+    output_log : dict
+        The output log of the command as filled by run_subprocess.
+
+    Returns
+    -------
+    bool
+        True if docker failed to start or run the container, e.g. due to a
+        transient error of the daemon.
+    """
+    # docker run exits with 125 if the daemon failed and reports the error on stderr.
+    return output_log.get('exit_code') == 125 and \
+        (output_log.get('stderr') or b'').lstrip().startswith(b'docker:')
+
+
+def decompress_output(raw_output: bytes) -> bytes:
+    """Decompress the output of a program if it is gzip-compressed.
+
+    Parameters
+    ----------
+    raw_output : bytes
+        The output of the program, compressed or uncompressed.
+
+    Returns
+    -------
+    bytes
+        The decompressed output, or the unchanged output if it does not start with the gzip magic number.
+
+    Raises
+    ------
+    OSError
+        If the output is not a valid gzip stream.
+    EOFError
+        If the gzip stream is truncated.
+    """
+    if raw_output[:2] == b'\x1f\x8b':
+        return gzip.decompress(raw_output)
+    return raw_output
+
+
+def format_phase_times(phase_times: dict) -> list:
+    """Format the time spent in each phase of a match as a table, starting with the dominating phase.
+
+    Parameters
+    ----------
+    phase_times : dict
+        A mapping between the names of the phases and the seconds spent in them.
+
+    Returns
+    -------
+    list
+        The lines of the table, one per phase and a final line with the total.
+    """
+    total_time = sum(phase_times.values())
+    lines = []
+    for phase, phase_time in sorted(phase_times.items(), key=lambda item: item[1], reverse=True):
+        share = 100 * phase_time / total_time if total_time else 0.0
+        lines.append('{:<16}{:>10.2f}s{:>8.1f}%'.format(phase, phase_time, share))
+    lines.append('{:<16}{:>10.2f}s'.format('total', total_time))
+    return lines
+
+
+def truncate_output(raw_output: bytes, limit: int) -> str:
+    """Decode the captured output of a program such that it can be safely serialized.
+
+    Parameters
+    ----------
+    raw_output : bytes
+        The captured output, may be None.
+    limit : int
+        Maximal number of bytes that are kept. No limit is set if 0 or None.
+
+    Returns
+    -------
+    str
+        The decoded output, with all bytes that are not valid UTF-8 replaced
+        by escape sequences. None if no output was captured.
+    """
+    if raw_output is None:
+        return None
+    if limit and len(raw_output) > limit:
+        return raw_output[:limit].decode(errors='backslashreplace') + \
+            '... ({} more bytes truncated)'.format(len(raw_output) - limit)
+    return raw_output.decode(errors='backslashreplace')
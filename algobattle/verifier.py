@@ -4,12 +4,41 @@ import logging
 logger = logging.getLogger('algobattle.verifier')
 
 
+class Score:
+    """Score class, an approximation ratio together with a breakdown into named components.
+
+    May be returned by calculate_approximation_ratio in place of a float for
+    problems with several objectives, such that the components are reported
+    alongside the approximation ratio. Battles are always rated on the
+    approximation ratio alone.
+    """
+
+    def __init__(self, score: float, components: dict = None) -> None:
+        self.score = score
+        self.components = components or dict()
+
+    def __repr__(self) -> str:
+        return 'Score({}, {})'.format(self.score, self.components)
+
+
+class VerificationError(Exception):
+    """Raised by the checks of a verifier to reject an instance or a solution with a human-readable reason.
+
+    The message is reported in the record of the fight, e.g. "indices out of
+    range: [42]". Returning False from a check rejects without a specific
+    reason.
+    """
+
+
 class Verifier(ABC):
     """Verifier class, responsible for semantically checking parsed instances and solutions.
 
     Checks the validity of a solution against a given instance
     as well as verifying that a solution is of a demanded quality, which
     usually refers to the solution size.
+
+    Instead of returning False, the verify methods may raise a
+    VerificationError that explains why the check failed.
     """
 
     def verify_semantics_of_instance(self, instance: any, instance_size: int) -> bool:
@@ -106,9 +135,102 @@ class Verifier(ABC):
 
         Returns
         -------
-        float
+        float or Score
             Returns the solution quality of the solver solution relative to the generator solution.
             The return value is the approximation ratio of the solver against
-            the generator (1 if optimal, 0 if failed, else >1).
+            the generator (1 if optimal, 0 if failed, else >1), optionally
+            wrapped in a Score object together with named sub-scores.
         """
         raise NotImplementedError
+
+
+class DecisionSolution:
+    """DecisionSolution class, the solution of a decision problem.
+
+    Consists of the claimed answer and a certificate that proves it. The
+    parsers of decision problems return objects of this class from
+    parse_solution.
+    """
+
+    def __init__(self, answer: bool, certificate: any = None) -> None:
+        self.answer = answer
+        self.certificate = certificate
+
+    def __repr__(self) -> str:
+        return 'DecisionSolution({}, {})'.format(self.answer, self.certificate)
+
+
+class DecisionVerifier(Verifier):
+    """DecisionVerifier class, a verifier for decision problems whose solutions are DecisionSolution objects.
+
+    The generator provides the true answer of its instance together with a
+    certificate. A solver receives the full score if it claims the same
+    answer and its certificate is valid, and fails otherwise. Answers of the
+    generator that need no certificate are not proven, so a solver that claims
+    a different answer with a valid certificate proves the generator wrong and
+    receives the full score as well. Problem authors only need to implement
+    verify_certificate.
+    """
+
+    def verify_semantics_of_solution(self, solution: any, instance_size: int, solution_type: bool) -> bool:
+        if not isinstance(solution, DecisionSolution):
+            logger.error('The solution is not a decision!')
+            return False
+        return True
+
+    def requires_certificate(self, answer: bool) -> bool:
+        """Decide whether an answer needs to be proven by a certificate.
+
+        By default, only positive answers need a certificate.
+
+        Parameters
+        ----------
+        answer : bool
+            The claimed answer.
+
+        Returns
+        -------
+        bool
+            Returns True if a solution with this answer needs to contain a certificate.
+        """
+        return answer
+
+    @abstractmethod
+    def verify_certificate(self, instance: any, answer: bool, certificate: any, instance_size: int) -> bool:
+        """Check whether a certificate proves the claimed answer for an instance.
+
+        Parameters
+        ----------
+        instance : any
+            The syntactically checked instance.
+        answer : bool
+            The claimed answer.
+        certificate : any
+            The certificate of the answer, not None.
+        instance_size : int
+            The maximum instance size.
+
+        Returns
+        -------
+        bool
+            Returns True if the certificate is valid.
+        """
+        raise NotImplementedError
+
+    def verify_solution_against_instance(self, instance: any, solution: any, instance_size: int, solution_type: bool) -> bool:
+        if solution.certificate is None:
+            if self.requires_certificate(solution.answer):
+                logger.error('The answer {} is missing a certificate!'.format(solution.answer))
+                return False
+            return True
+        return self.verify_certificate(instance, solution.answer, solution.certificate, instance_size)
+
+    def calculate_approximation_ratio(self, instance: any, instance_size: int,
+                                      generator_solution: any, solver_solution: any) -> float:
+        if solver_solution.answer == generator_solution.answer:
+            return 1.0
+        if solver_solution.certificate is not None and generator_solution.certificate is None \
+                and self.verify_certificate(instance, solver_solution.answer, solver_solution.certificate, instance_size):
+            logger.info('The certificate of the solver disproves the uncertified answer of the generator.')
+            return 1.0
+        return 0.0
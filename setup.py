@@ -6,5 +6,9 @@ setup(
     packages=['algobattle'],
     scripts=['scripts/battle'],
     python_requires='>=3.6',
+    extras_require={
+        'toml': ['toml; python_version < "3.11"'],
+        'yaml': ['PyYAML']
+    },
     include_package_data=True
 )
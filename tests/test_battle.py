@@ -0,0 +1,156 @@
+""" Tests for the programmatic battle interface.
+"""
+import unittest
+import logging
+import importlib
+import importlib.util
+import os
+import tempfile
+from unittest import mock
+
+from algobattle.battle import BattleResult, run_battle, run_solver_on_instance, check_battle_setup, ExitCode, \
+    BuildError, BattleSetupError
+
+logging.disable(logging.CRITICAL)
+
+
+class Battletests(unittest.TestCase):
+    def setUp(self) -> None:
+        Problem = importlib.import_module('algobattle.problems.testsproblem')
+        self.problem = Problem.Problem()
+        self.tests_path = Problem.__file__[:-12]  # remove /__init__.py
+
+    def test_run_battle_invalid_arguments(self):
+        with self.assertRaises(ValueError):
+            run_battle('foo', self.tests_path + '/generator', self.tests_path + '/solver')
+
+        with self.assertRaises(BattleSetupError):
+            run_battle(self.problem, self.tests_path + '/generator', self.tests_path + '/solver', battle_type='foo')
+
+        with self.assertRaises(BattleSetupError):
+            run_battle(self.problem, [self.tests_path + '/generator'],
+                       [self.tests_path + '/solver', self.tests_path + '/solver'])
+
+        with self.assertRaises(BattleSetupError):
+            run_battle(self.problem, self.tests_path + '/generator', self.tests_path + '/solver', jobs=0)
+
+    def test_run_battle_runtime_errors(self):
+        # Errors raised while running the fights are not mistaken for invalid arguments
+        match = mock.Mock(build_successful=True, fight_listeners=[])
+        match.run.side_effect = ValueError('foo')
+        with mock.patch('algobattle.battle.Match', return_value=match):
+            with self.assertRaises(ValueError) as context:
+                run_battle(self.problem, self.tests_path + '/generator', self.tests_path + '/solver')
+        self.assertNotIsInstance(context.exception, BattleSetupError)
+
+    def test_run_solver_on_instance_invalid_arguments(self):
+        with self.assertRaises(ValueError):
+            run_solver_on_instance('foo', self.tests_path + '/solver', 'instance.txt', 1)
+
+        with self.assertRaises(ValueError):
+            run_solver_on_instance(self.problem, self.tests_path + '/solver', 'instance.txt', 0)
+
+    def test_battle_result_calculate_points(self):
+        result = BattleResult('iterated', 2, ['0', '1'], {('0', '1'): [20, 10], ('1', '0'): [10, 20]}, [])
+        self.assertEqual(result.calculate_points(100), {'0': 50, '1': 50})
+
+    def test_battle_result_disqualified_teams(self):
+        fights = [{'generating_team': '0', 'solving_team': '1', 'score': 0.0, 'failed_role': 'solver'},
+                  {'generating_team': '1', 'solving_team': '0', 'score': 1.0, 'failed_role': None}]
+        result = BattleResult('iterated', 1, ['0', '1'], {}, fights)
+        self.assertEqual(result.disqualified_teams(), ['1'])
+
+        # The generator of team 1 fails in every fight
+        fights[0]['failed_role'] = None
+        fights[1]['failed_role'] = 'generator'
+        self.assertEqual(result.disqualified_teams(), ['1'])
+
+        # Fights without a program to blame, e.g. because the verifier crashed, disqualify no one
+        fights[1]['failed_role'] = None
+        fights[0]['score'] = 0.0
+        self.assertEqual(result.disqualified_teams(), [])
+
+    def test_check_battle_setup(self):
+        self.assertEqual(check_battle_setup(self.problem, self.tests_path + '/generator', self.tests_path + '/solver'), [])
+        self.assertEqual(check_battle_setup(self.problem, [None], [self.tests_path + '/solver']), [])
+
+        issues = check_battle_setup('foo', self.tests_path, self.tests_path + '/bar')
+        self.assertEqual(len(issues), 3)
+
+        with tempfile.TemporaryDirectory() as tmpdir:
+            config = os.path.join(tmpdir, 'config.ini')
+            with open(config, 'w') as config_file:
+                config_file.write('[run_parameters]\ntimeout_build = foo')
+            issues = check_battle_setup(self.problem, self.tests_path + '/generator', self.tests_path + '/solver',
+                                        config=config)
+            self.assertIn('The option "run_parameters.timeout_build" of the config file is not an integer.', issues)
+            self.assertIn('The config file is missing the option "run_parameters.timeout_solver".', issues)
+
+            issues = check_battle_setup(self.problem, self.tests_path + '/generator', self.tests_path + '/solver',
+                                        config=os.path.join(tmpdir, 'missing.ini'))
+            self.assertEqual(len(issues), 2)
+
+        issues = check_battle_setup(self.problem, self.tests_path + '/generator', self.tests_path + '/solver',
+                                    team_names=['0', '1'])
+        self.assertEqual(issues, ['The number of provided solver paths (1) and team names (2) is not equal.'])
+
+    @unittest.skipUnless(importlib.util.find_spec('yaml'), 'Reading YAML config files requires the PyYAML package.')
+    def test_check_battle_setup_yaml(self):
+        # The same issues are found regardless of the format of the config file
+        with tempfile.TemporaryDirectory() as tmpdir:
+            ini_config = os.path.join(tmpdir, 'config.ini')
+            with open(ini_config, 'w') as config_file:
+                config_file.write('[run_parameters]\ntimeout_build = foo')
+            yaml_config = os.path.join(tmpdir, 'config.yaml')
+            with open(yaml_config, 'w') as config_file:
+                config_file.write('run_parameters:\n  timeout_build: foo\n')
+            self.assertEqual(check_battle_setup(self.problem, self.tests_path + '/generator',
+                                                self.tests_path + '/solver', config=yaml_config),
+                             check_battle_setup(self.problem, self.tests_path + '/generator',
+                                                self.tests_path + '/solver', config=ini_config))
+
+    def test_run_battle_jobs(self):
+        # Only averaged battles run fights in parallel and need the resources of several fights
+        for battle_type, jobs in [('averaged', 4), ('iterated', 1)]:
+            with mock.patch('algobattle.battle.Match', return_value=mock.Mock(build_successful=False)) as match:
+                with self.assertRaises(BuildError):
+                    run_battle(self.problem, self.tests_path + '/generator', self.tests_path + '/solver',
+                               battle_type=battle_type, jobs=4)
+                self.assertEqual(match.call_args[1]['jobs'], jobs)
+
+    def test_run_battle_invalid_battle_type_options(self):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            config = os.path.join(tmpdir, 'config.ini')
+            with open(config, 'w') as config_file:
+                config_file.write('[run_parameters]\nstep_strategy = foo\naggregation_method = foo')
+            for battle_type in ['iterated', 'averaged']:
+                with mock.patch('algobattle.battle.Match') as match:
+                    with self.assertRaises(ValueError):
+                        run_battle(self.problem, self.tests_path + '/generator', self.tests_path + '/solver',
+                                   config=config, battle_type=battle_type)
+                    # No docker image is built
+                    match.assert_not_called()
+
+    def test_run_battle_invalid_checkpoint(self):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            with self.assertRaises(ValueError):
+                run_battle(self.problem, self.tests_path + '/generator', self.tests_path + '/solver',
+                           resume=os.path.join(tmpdir, 'missing.json'))
+
+            with self.assertRaises(ValueError):
+                run_battle(self.problem, self.tests_path + '/generator', self.tests_path + '/solver',
+                           battle_type='comparison', checkpoint=os.path.join(tmpdir, 'state.json'))
+
+    def test_run_battle_invalid_instance_sizes(self):
+        for instance_sizes in [None, [], [0], [5, 10**9], ['5']]:
+            with self.assertRaises(ValueError):
+                run_battle(self.problem, self.tests_path + '/generator', self.tests_path + '/solver',
+                           battle_type='instance_sizes', instance_sizes=instance_sizes)
+
+    def test_exit_codes(self):
+        self.assertEqual(ExitCode.SUCCESS, 0)
+        self.assertEqual(len({exit_code.value for exit_code in ExitCode}), len(ExitCode))
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,149 @@
+""" Tests for the battle types.
+"""
+import unittest
+import logging
+import configparser
+
+import algobattle.battle_wrapper
+from algobattle.battle_wrapper import BattleWrapper, IteratedBattleWrapper, AveragedBattleWrapper, get_battle_types, \
+    register_battle_type, aggregate, DefaultStepStrategy, LinearStepStrategy, DoublingStepStrategy, BinarySearchStepStrategy
+
+logging.disable(logging.CRITICAL)
+
+
+class TournamentBattleWrapper(BattleWrapper):
+    name = 'tournament'
+
+    def run(self, match):
+        return 0
+
+    def valuation(self, battle_result):
+        return battle_result
+
+
+class BattleWrappertests(unittest.TestCase):
+    def test_get_battle_types(self):
+        battle_types = get_battle_types()
+        self.assertIs(battle_types['iterated'], IteratedBattleWrapper)
+        self.assertIs(battle_types['averaged'], AveragedBattleWrapper)
+        # Subclasses are only available once they are registered
+        self.assertNotIn('tournament', battle_types)
+
+        self.assertIs(register_battle_type(TournamentBattleWrapper), TournamentBattleWrapper)
+        self.addCleanup(algobattle.battle_wrapper._battle_types.pop, 'tournament')
+        self.assertIs(get_battle_types()['tournament'], TournamentBattleWrapper)
+
+    def test_register_battle_type(self):
+        with self.assertRaises(ValueError):
+            register_battle_type(int)
+        with self.assertRaises(ValueError):
+            register_battle_type(BattleWrapper)
+
+        # The built-in battle types can not be replaced
+        class OtherIteratedBattleWrapper(IteratedBattleWrapper):
+            pass
+        register_battle_type(OtherIteratedBattleWrapper)
+        self.assertIs(get_battle_types()['iterated'], IteratedBattleWrapper)
+
+    def test_valuation(self):
+        self.assertEqual(IteratedBattleWrapper().valuation(20), 20)
+        self.assertEqual(AveragedBattleWrapper().valuation([1.0, 2.0, 3.0]), 1.5)
+
+    def test_aggregate_mean(self):
+        self.assertEqual(aggregate([1.0, 2.0, 6.0]), 3.0)
+
+    def test_aggregate_median(self):
+        # Odd number of fights
+        self.assertEqual(aggregate([1.0, 5.0, 2.0], 'median'), 2.0)
+        # Even number of fights
+        self.assertEqual(aggregate([1.0, 5.0, 2.0, 4.0], 'median'), 3.0)
+        # Ties
+        self.assertEqual(aggregate([2.0, 1.0, 2.0, 9.0], 'median'), 2.0)
+        self.assertEqual(aggregate([1.5, 1.5, 1.5], 'median'), 1.5)
+
+    def test_aggregate_trimmed_mean(self):
+        self.assertEqual(aggregate([100.0, 1.0, 2.0, 3.0, 0.0], 'trimmed_mean', 0.2), 2.0)
+        # Too few values to trim any of them
+        self.assertEqual(aggregate([1.0, 2.0], 'trimmed_mean', 0.2), 1.5)
+
+        with self.assertRaises(ValueError):
+            aggregate([1.0], 'foo')
+
+    def test_config_unchanged(self):
+        config = configparser.ConfigParser()
+        battle_wrapper = IteratedBattleWrapper(config)
+        self.assertEqual(config.sections(), [])
+        self.assertTrue(battle_wrapper.config.has_section('run_parameters'))
+
+        config.read_dict({'run_parameters': {'step_strategy': 'linear'}})
+        self.assertEqual(IteratedBattleWrapper(config).step_strategy, 'linear')
+
+    def test_averaged_battle_wrapper_config(self):
+        config = configparser.ConfigParser()
+        config.read_dict({'run_parameters': {'aggregation_method': 'median'}})
+        battle_wrapper = AveragedBattleWrapper(config)
+        self.assertEqual(battle_wrapper.valuation([1.0, 1.0, 100.0]), 3.0)
+        self.assertEqual(battle_wrapper.valuation([0.0, 0.0, 1.0]), 0.0)
+
+        config.read_dict({'run_parameters': {'aggregation_method': 'foo'}})
+        with self.assertRaises(ValueError):
+            AveragedBattleWrapper(config)
+
+        config.read_dict({'run_parameters': {'aggregation_method': 'trimmed_mean', 'trim_fraction': '0.5'}})
+        with self.assertRaises(ValueError):
+            AveragedBattleWrapper(config)
+
+    def run_step_strategy(self, step_strategy, threshold):
+        # A solver that solves exactly the instances up to the threshold
+        instance_sizes = []
+        n = step_strategy.n_start
+        while n is not None:
+            instance_sizes.append(n)
+            n = step_strategy.next_size(n, n <= threshold)
+        return step_strategy.maximum_reached_n, instance_sizes
+
+    def test_default_step_strategy(self):
+        self.assertEqual(self.run_step_strategy(DefaultStepStrategy(1, 1000), 20),
+                         (20, [1, 2, 6, 15, 31, 16, 20, 29, 21]))
+        self.assertEqual(self.run_step_strategy(DefaultStepStrategy(1, 1000), 0)[0], 0)
+        self.assertEqual(self.run_step_strategy(DefaultStepStrategy(1, 1000), 2000)[0], 1000)
+
+    def test_linear_step_strategy(self):
+        self.assertEqual(self.run_step_strategy(LinearStepStrategy(1, 1000, 5), 20), (16, [1, 6, 11, 16, 21]))
+        self.assertEqual(self.run_step_strategy(LinearStepStrategy(1, 10), 2000), (10, list(range(1, 10))))
+
+    def test_doubling_step_strategy(self):
+        self.assertEqual(self.run_step_strategy(DoublingStepStrategy(1, 1000), 20), (16, [1, 2, 4, 8, 16, 32]))
+        self.assertEqual(self.run_step_strategy(DoublingStepStrategy(1, 1000), 2000)[0], 1000)
+
+    def test_binary_search_step_strategy(self):
+        for threshold in [0, 1, 2, 20, 381, 512, 998]:
+            maximum_reached_n, instance_sizes = self.run_step_strategy(BinarySearchStepStrategy(1, 1000), threshold)
+            self.assertEqual(maximum_reached_n, threshold)
+            self.assertLessEqual(len(instance_sizes), 20)
+        self.assertEqual(self.run_step_strategy(BinarySearchStepStrategy(1, 1000), 20),
+                         (20, [1, 2, 4, 8, 16, 32, 24, 20, 22, 21]))
+        self.assertEqual(self.run_step_strategy(BinarySearchStepStrategy(1, 1000), 2000)[0], 1000)
+        # Instances below n_start are never tried
+        self.assertEqual(self.run_step_strategy(BinarySearchStepStrategy(5, 1000), 3), (0, [5]))
+
+    def test_iterated_battle_wrapper_config(self):
+        config = configparser.ConfigParser()
+        self.assertIsInstance(IteratedBattleWrapper(config).create_step_strategy(1, 100), DefaultStepStrategy)
+
+        config.read_dict({'run_parameters': {'step_strategy': 'linear', 'step_size': '3'}})
+        step_strategy = IteratedBattleWrapper(config).create_step_strategy(1, 100)
+        self.assertIsInstance(step_strategy, LinearStepStrategy)
+        self.assertEqual(step_strategy.step_size, 3)
+
+        config.read_dict({'run_parameters': {'step_strategy': 'foo'}})
+        with self.assertRaises(ValueError):
+            IteratedBattleWrapper(config)
+
+        config.read_dict({'run_parameters': {'step_strategy': 'linear', 'step_size': '0'}})
+        with self.assertRaises(ValueError):
+            IteratedBattleWrapper(config)
+
+
+if __name__ == '__main__':
+    unittest.main()
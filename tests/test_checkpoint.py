@@ -0,0 +1,50 @@
+""" Tests for the checkpoints of matches.
+"""
+import unittest
+import logging
+import os
+import tempfile
+
+from algobattle.checkpoint import Checkpoint
+
+logging.disable(logging.CRITICAL)
+
+
+class Checkpointtests(unittest.TestCase):
+    def test_save_and_load(self):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            path = os.path.join(tmpdir, 'state.json')
+            checkpoint = Checkpoint(path, 42, 'averaged')
+            checkpoint.add_fight({'instance_size': 10, 'score': 1.0})
+            checkpoint.add_fight({'instance_size': 10, 'score': 1.5})
+
+            loaded_checkpoint = Checkpoint.load(path)
+            self.assertEqual(loaded_checkpoint.seed, 42)
+            self.assertEqual(loaded_checkpoint.battle_type, 'averaged')
+            self.assertEqual(loaded_checkpoint.fights, [{'instance_size': 10, 'score': 1.0},
+                                                        {'instance_size': 10, 'score': 1.5}])
+            self.assertFalse(os.path.exists(path + '.tmp'))
+
+    def test_add_fight_skips_replayed_fights(self):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            path = os.path.join(tmpdir, 'state.json')
+            checkpoint = Checkpoint(path, 42, 'averaged', [{'score': 1.0}])
+            checkpoint.add_fight({'score': 1.0})
+            self.assertFalse(os.path.exists(path))
+            checkpoint.add_fight({'score': 2.0})
+            self.assertEqual(Checkpoint.load(path).fights, [{'score': 1.0}, {'score': 2.0}])
+
+    def test_load_invalid_file(self):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            with self.assertRaises(ValueError):
+                Checkpoint.load(os.path.join(tmpdir, 'missing.json'))
+
+            path = os.path.join(tmpdir, 'state.json')
+            with open(path, 'w') as state_file:
+                state_file.write('{"seed": 42}')
+            with self.assertRaises(ValueError):
+                Checkpoint.load(path)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,118 @@
+""" Tests for the interaction between the generator and the solver.
+"""
+import unittest
+import logging
+import importlib
+import os
+import sys
+import time
+
+import algobattle
+from algobattle.interaction import InteractiveProcess
+from algobattle.match import Match
+from algobattle.problem import Problem
+from algobattle.team import Team
+
+logging.disable(logging.CRITICAL)
+
+# Plays both roles of a guessing game, depending on the image name that is appended to the command.
+GUESSING_GAME = '''
+import sys
+if 'generator-0' in sys.argv:
+    n = int(input())
+    secret = n // 3 + 1
+    print(n, flush=True)
+    while True:
+        guess = int(input())
+        print('correct' if guess == secret else 'higher' if guess < secret else 'lower', flush=True)
+else:
+    low, high = 1, int(input())
+    while True:
+        guess = (low + high) // 2
+        print(guess, flush=True)
+        answer = input()
+        if answer == 'higher':
+            low = guess + 1
+        elif answer == 'lower':
+            high = guess - 1
+'''
+
+
+class GuessingProblem(Problem):
+    name = 'Guessing'
+    n_start = 1
+    parser = None
+    verifier = None
+    approximable = False
+    interactive = True
+    interaction_rounds = 10
+
+    def interact(self, instance_size, round, sender, message):
+        if sender == 'generator' and message == b'correct':
+            return None
+        return message
+
+    def rate_interaction(self, instance_size, transcript):
+        return 1.0 if transcript[-1] == ('generator', b'correct') else 0.0
+
+
+class Interactiontests(unittest.TestCase):
+    def setUp(self) -> None:
+        Problem = importlib.import_module('algobattle.problems.testsproblem')
+        self.tests_path = Problem.__file__[:-12]  # remove /__init__.py
+
+        self.config = os.path.join(os.path.dirname(os.path.abspath(algobattle.__file__)), 'config', 'config.ini')
+
+    def test_interactive_process(self):
+        echo = InteractiveProcess([sys.executable, '-c', 'print(input()[::-1], flush=True)'], 10)
+        self.assertTrue(echo.send(b'foo'))
+        self.assertEqual(echo.receive(), b'oof')
+        self.assertIsNone(echo.receive())
+        output_log = echo.stop()
+        self.assertEqual(output_log['stdout'], b'oof\n')
+        self.assertEqual(output_log['stderr'], b'')
+        self.assertEqual(output_log['exit_code'], 0)
+        self.assertGreaterEqual(output_log['wall_time'], 0)
+
+        sleeper = InteractiveProcess([sys.executable, '-c', 'import time; time.sleep(100)'], 0.5)
+        self.assertIsNone(sleeper.receive())
+        self.assertGreaterEqual(sleeper.waiting_time, 0.5)
+        sleeper.stop()
+
+    def test_run_interaction(self):
+        match = Match(GuessingProblem(), self.config, [Team('0', self.tests_path + '/generator',
+                                                            self.tests_path + '/solver')])
+        match.base_build_command = [sys.executable, '-c', GUESSING_GAME]
+        match.generator_run_options = []
+        match.solver_run_options = []
+        match.generating_team = '0'
+        match.solving_team = '0'
+
+        self.assertEqual(match._run_interaction(30), 1.0)
+        self.assertTrue(match.fight_logs['generator']['stdout'].endswith(b'correct\n'))
+
+        # The secret can not be found within the given number of rounds
+        self.assertEqual(match._run_interaction(10000), 0.0)
+
+    def test_run_interaction_limits(self):
+        class StallingProblem(GuessingProblem):
+            def interact(self, instance_size, round, sender, message):
+                time.sleep(100)
+
+        match = Match(StallingProblem(), self.config, [Team('0', self.tests_path + '/generator',
+                                                            self.tests_path + '/solver')])
+        match.base_build_command = [sys.executable, '-c', GUESSING_GAME]
+        match.generator_run_options = []
+        match.solver_run_options = []
+        match.generating_team = '0'
+        match.solving_team = '0'
+        match.timeout_verification = 0.5
+
+        self.assertEqual(match._run_interaction(30), 0.0)
+        self.assertEqual(match.fight_failure, 'verification error')
+        self.assertIn('Exceeded the time limit', match.fight_error)
+        self.assertIsNone(match.fight_failed_role)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,39 @@
+""" Tests for the JsonlWriter class.
+"""
+import unittest
+import logging
+import io
+import json
+
+from algobattle.jsonl import JsonlWriter
+
+logging.disable(logging.CRITICAL)
+
+
+class JsonlWritertests(unittest.TestCase):
+    def setUp(self) -> None:
+        self.stream = io.StringIO()
+        self.writer = JsonlWriter(self.stream)
+
+    def test_write_fight(self):
+        self.writer.write_fight({'battle_type': 'iterated', 'instance_size': 5, 'generating_team': '0',
+                                 'solving_team': '1', 'score': 1.0, 'start_time': 0.0, 'elapsed_time': 1.5})
+        self.writer.write_fight({'battle_type': 'iterated', 'instance_size': 6, 'generating_team': '0',
+                                 'solving_team': '1', 'score': 0.0, 'start_time': 1.5, 'elapsed_time': 2.0})
+        lines = self.stream.getvalue().splitlines()
+        self.assertEqual(len(lines), 2)
+        self.assertEqual(json.loads(lines[0])['type'], 'fight')
+        self.assertEqual(json.loads(lines[0])['instance_size'], 5)
+        self.assertEqual(json.loads(lines[1])['score'], 0.0)
+
+    def test_write_summary(self):
+        self.writer.write_summary('iterated', {('0', '1'): [20, 10], ('1', '0'): [10, 20]}, {'0': 50, '1': 50})
+        summary = json.loads(self.stream.getvalue())
+        self.assertEqual(summary['type'], 'summary')
+        self.assertEqual(summary['results'][0], {'generating_team': '0', 'solving_team': '1', 'results': [20, 10]})
+        self.assertEqual(summary['points'], {'0': 50, '1': 50})
+        self.assertIsNone(summary['images'])
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -2,12 +2,21 @@
 """
 import unittest
 import logging
+import configparser
+import functools
 import importlib
 import os
+import random
+import tempfile
+import time
+from unittest import mock
 
 import algobattle
 from algobattle.match import Match
 from algobattle.team import Team
+from algobattle.verifier import Score, VerificationError
+from algobattle.battle_wrapper import BinarySearchStepStrategy
+from algobattle.util import ConfigError
 
 logging.disable(logging.CRITICAL)
 
@@ -25,6 +34,21 @@ class Matchtests(unittest.TestCase):
 
         self.match = Match(self.problem, self.config, [self.team])
 
+    def test_invalid_config(self):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            config = os.path.join(tmpdir, 'config.ini')
+            for key, value, message in [('timeout_build', 'foo', 'is not an integer'),
+                                        ('docker_retries', '-1', 'may not be negative'),
+                                        ('docker_retry_delay', '-1', 'may not be negative')]:
+                config_parser = configparser.ConfigParser()
+                config_parser.read(self.config)
+                config_parser['run_parameters'][key] = value
+                with open(config, 'w') as config_file:
+                    config_parser.write(config_file)
+                with self.assertRaises(ConfigError) as context:
+                    Match(self.problem, config, [self.team])
+                self.assertIn('"run_parameters.{}" of the config file {}'.format(key, message), str(context.exception))
+
     def test_build(self):
         self.assertTrue(self.match.build_successful)
 
@@ -43,6 +67,13 @@ class Matchtests(unittest.TestCase):
         match = Match(self.problem, self.config, 'foo')
         self.assertFalse(match.build_successful)
 
+        with tempfile.TemporaryDirectory() as tmpdir:
+            config_too_many_cpus = os.path.join(tmpdir, 'config.ini')
+            with open(self.config) as config_file, open(config_too_many_cpus, 'w') as new_config_file:
+                new_config_file.write(config_file.read() + '\ncpus_solver = 100000')
+            match_too_many_cpus = Match(self.problem, config_too_many_cpus, [self.team])
+            self.assertFalse(match_too_many_cpus.build_successful)
+
     def test_all_battle_pairs(self):
         team0 = Team('0', self.tests_path + '/generator', self.tests_path + '/solver')
         team1 = Team('1', self.tests_path + '/generator', self.tests_path + '/solver')
@@ -56,11 +87,343 @@ class Matchtests(unittest.TestCase):
     def test_run(self):
         self.assertEqual(self.match.run(battle_type='foo'), ({'Error': 'Unrecognized battle type'}))
 
+    def test_run_solver_comparison(self):
+        team0 = Team('0', self.tests_path + '/generator', self.tests_path + '/solver')
+        team1 = Team('1', None, self.tests_path + '/solver_execution_error')
+        match = Match(self.problem, self.config, [team0, team1])
+        self.assertEqual(match.run_solver_comparison(iterations=2, instance_size=1, generating_teams=['0']),
+                         {('0', '0'): [1.0, 1.0], ('0', '1'): [0.0, 0.0]})
+
+    def test_run_on_instance(self):
+        self.assertEqual(self.match.run_on_instance('i 1\ns 1 1 1'.encode(), 1, '0'), 1.0)
+        self.assertIsNone(self.match.run_on_instance('i 0\ns 1 1 1'.encode(), 1, '0'))
+
+    def test_run_on_instance_sizes(self):
+        team1 = Team('1', self.tests_path + '/generator', self.tests_path + '/solver')
+        match = Match(self.problem, self.config, [self.team, team1])
+        # The programs of the teams do not matter for the order of the fights
+        match.build_successful = True
+        match.team_names = ['0', '1']
+        match.single_player = False
+        match._one_fight = lambda instance_size: float(instance_size)
+        self.assertEqual(match.run_on_instance_sizes([5, 50], iterations=2),
+                         {('0', '1'): [5.0, 5.0, 50.0, 50.0], ('1', '0'): [5.0, 5.0, 50.0, 50.0]})
+        self.assertEqual(match.battle_type, 'instance_sizes')
+
+    def test_check_generator_output(self):
+        self.assertIsNotNone(self.match._check_generator_output(b'i 1\ns 1 1 1', 2))
+
+        # Problems that determine the size of instances reject instances of the wrong size
+        self.problem.instance_size = len
+        self.assertIsNotNone(self.match._check_generator_output(b'i 1\ns 1 1 1', 1))
+        self.assertIsNone(self.match._check_generator_output(b'i 1\ns 1 1 1', 2))
+        self.assertIn('instance of size 1 instead of the requested size 2', self.match.fight_error)
+
+        # Problems failing to determine the size of an instance fail the fight of the generator only
+        def instance_size(instance):
+            raise ValueError('foo')
+        self.problem.instance_size = instance_size
+        self.assertIsNone(self.match._check_generator_output(b'i 1\ns 1 1 1', 1))
+        self.assertIn('could not be determined: foo', self.match.fight_error)
+
+    def test_seed(self):
+        match0 = Match(self.problem, self.config, [self.team], seed=42)
+        match1 = Match(self.problem, self.config, [self.team], seed=42)
+        self.assertEqual(match0.seed, 42)
+        self.assertEqual([match0.seed_generator.randrange(2**32) for _ in range(5)],
+                         [match1.seed_generator.randrange(2**32) for _ in range(5)])
+        self.assertIsNotNone(self.match.seed)
+
+    def test_resource_limits(self):
+        self.assertEqual(self.match.resource_limits(), {'generator': {'cpus': 1.0, 'memory': 2000},
+                                                        'solver': {'cpus': 1.0, 'memory': 2000}})
+        self.assertIn('--memory=2000mb', self.match.generator_run_options)
+        self.assertIn('--cpus=1.0', self.match.solver_run_options)
+
+        # The legacy option cpus applies to both programs and may be fractional
+        with tempfile.TemporaryDirectory() as tmpdir:
+            config = os.path.join(tmpdir, 'config.ini')
+            config_parser = configparser.ConfigParser()
+            config_parser.read(self.config)
+            del config_parser['run_parameters']['cpus_generator']
+            del config_parser['run_parameters']['cpus_solver']
+            config_parser['run_parameters']['cpus'] = '0.5'
+            with open(config, 'w') as config_file:
+                config_parser.write(config_file)
+            match = Match(self.problem, config, [self.team])
+        self.assertEqual(match.resource_limits(), {'generator': {'cpus': 0.5, 'memory': 2000},
+                                                   'solver': {'cpus': 0.5, 'memory': 2000}})
+
+    def test_write_fight_logs(self):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            self.match.log_directory = tmpdir
+            self.match.fight_logs = {'generator': {'stdout': b'i 1', 'stderr': b'\xff' * 5000},
+                                     'solver': {'stdout': None, 'stderr': None}}
+            fight_logs = self.match._write_fight_logs()
+            self.assertEqual(fight_logs['generator']['stdout'], 'i 1')
+            self.assertTrue(fight_logs['generator']['stderr'].endswith('(904 more bytes truncated)'))
+            self.assertEqual(fight_logs['solver'], {'stdout': None, 'stderr': None})
+
+            with open(os.path.join(tmpdir, 'fight0_generator.stderr'), 'rb') as log_file:
+                self.assertEqual(log_file.read(), b'\xff' * 5000)
+            self.assertFalse(os.path.exists(os.path.join(tmpdir, 'fight0_solver.stdout')))
+
+    def test_save_failing_instance(self):
+        self.match.generating_team = '0'
+        self.match.solving_team = '1'
+        self.match.fight_index = 3
+        self.match.fight_failure = 'timeout'
+        self.match.fight_instance = b'i 1\ns 1 1 1'
+        self.match._save_failing_instance(4)
+        self.assertEqual(self.match.failing_instances, [])
+
+        with tempfile.TemporaryDirectory() as tmpdir:
+            self.match.failing_instances_directory = tmpdir
+            self.match._save_failing_instance(4)
+            instance_path = os.path.join(tmpdir, 'fight2_0_vs_1_n4.txt')
+            self.assertEqual(self.match.failing_instances, [{'generating_team': '0', 'solving_team': '1',
+                                                             'instance_size': 4, 'failure': 'timeout',
+                                                             'path': instance_path}])
+            with open(instance_path, 'rb') as instance_file:
+                self.assertEqual(instance_file.read(), b'i 1\ns 1 1 1')
+
+    def test_unpack_score(self):
+        self.assertEqual(self.match._unpack_score(1.5), 1.5)
+        self.assertIsNone(self.match.fight_score_components)
+
+        self.assertEqual(self.match._unpack_score(Score(0.7, {'feasibility': 1.0, 'quality': 0.7})), 0.7)
+        self.assertEqual(self.match.fight_score_components, {'feasibility': 1.0, 'quality': 0.7})
+
+    def test_verify(self):
+        def verify_indices(solution):
+            if solution > 10:
+                raise VerificationError('indices out of range: [{}]'.format(solution))
+            return solution > 0
+
+        self.assertTrue(self.match._verify('check_semantics', verify_indices, 5))
+        self.assertIsNone(self.match.fight_rejection_reason)
+
+        self.assertFalse(self.match._verify('check_semantics', verify_indices, 0))
+        self.assertEqual(self.match.fight_rejection_reason, 'verify_indices: the check returned False')
+
+        self.assertFalse(self.match._verify('check_semantics', verify_indices, 42))
+        self.assertEqual(self.match.fight_rejection_reason, 'verify_indices: indices out of range: [42]')
+        self.assertIsNone(self.match.fight_error)
+
+    def test_verify_phase_times(self):
+        def slow_check(solution):
+            time.sleep(0.2)
+            return 1.0
+
+        self.match.phase_times = {phase: 0.0 for phase in self.match.phase_times}
+        self.match._verify('calculate_score', slow_check, 1)
+        self.assertGreaterEqual(self.match.phase_times['calculate_score'], 0.2)
+        self.assertEqual(self.match.phase_times['check_semantics'], 0.0)
+
+        # The phase does not depend on the name of the method
+        self.assertTrue(self.match._verify('check_semantics', functools.partial(lambda offset, solution: True, 0), 1))
+        self.assertGreater(self.match.phase_times['check_semantics'], 0.0)
+
+    def test_run_fights_in_parallel(self):
+        match = Match(self.problem, self.config, [self.team], seed=42, jobs=2)
+
+        def fight(instance_size):
+            seed = match.seed_generator.randrange(2**32)
+            # The first fight finishes last
+            time.sleep(0.5 if match.fight_index == 0 else 0)
+            match.phase_times['solve'] += 1.0
+            for listener in match.fight_listeners:
+                listener({'instance_size': instance_size, 'seed': seed})
+            return float(match.fight_index)
+
+        match._one_fight = fight
+        match.phase_times['solve'] = 0.0
+        fight_records = []
+        match.fight_listeners.append(fight_records.append)
+
+        self.assertEqual(match._run_fights_in_parallel([5, 6, 7, 8]), [0.0, 1.0, 2.0, 3.0])
+        seed_generator = random.Random(42)
+        self.assertEqual(fight_records, [{'instance_size': instance_size, 'seed': seed_generator.randrange(2**32)}
+                                         for instance_size in [5, 6, 7, 8]])
+        self.assertEqual(match.fight_index, 4)
+        # The phase times of the forked fights are added up in the parent
+        self.assertEqual(match.phase_times['solve'], 4.0)
+
+    def test_run_fights_in_parallel_failure_records(self):
+        match = Match(self.problem, self.config, [self.team], seed=42, jobs=2)
+
+        def fight(instance_size):
+            seed = match.seed_generator.randrange(2**32)
+            if instance_size == 6:
+                raise RuntimeError('foo')
+            if instance_size == 7:
+                os._exit(1)
+            for listener in match.fight_listeners:
+                listener({'instance_size': instance_size, 'seed': seed})
+            return 1.0
+
+        match._one_fight = fight
+        fight_records = []
+        match.fight_listeners.append(fight_records.append)
+
+        self.assertEqual(match._run_fights_in_parallel([5, 6, 7, 8]), [1.0, 0.0, 0.0, 1.0])
+        # Fights that raised an exception or whose process crashed are recorded as well
+        self.assertEqual([record['instance_size'] for record in fight_records], [5, 6, 7, 8])
+        seed_generator = random.Random(42)
+        self.assertEqual([record['seed'] for record in fight_records],
+                         [seed_generator.randrange(2**32) for _ in range(4)])
+        self.assertIn('foo', fight_records[1]['error'])
+        self.assertIn('terminated unexpectedly', fight_records[2]['error'])
+        self.assertEqual(fight_records[2]['score'], 0.0)
+
+    def test_run_container_retries(self):
+        self.match.docker_retry_delay = 0
+        daemon_error = {'stdout': b'', 'stderr': b'docker: Error response from daemon: Cannot connect.', 'exit_code': 125}
+        results = [daemon_error, daemon_error, {'stdout': b'i 1', 'stderr': b'', 'exit_code': 0}]
+
+        def run_subprocess(run_command, input, timeout, output_log=None, container_name=None):
+            output_log.update(results.pop(0))
+            return output_log['stdout'], output_log['stderr']
+
+        with mock.patch('algobattle.match.run_subprocess', side_effect=run_subprocess) as run_subprocess_mock:
+            self.assertEqual(self.match._run_container('generator', 'generator-0', b'1', 1), b'i 1')
+            self.assertEqual(run_subprocess_mock.call_count, 3)
+            self.assertIsNone(self.match.fight_error)
+
+            # Program failures are not retried
+            results = [{'stdout': b'', 'stderr': b'', 'exit_code': 125}]
+            self.assertEqual(self.match._run_container('solver', 'solver-0', b'1', 1), b'')
+            self.assertEqual(run_subprocess_mock.call_count, 4)
+
+            results = [daemon_error] * 4
+            self.assertIsNone(self.match._run_container('solver', 'solver-0', b'1', 1))
+            self.assertEqual(run_subprocess_mock.call_count, 8)
+            self.assertIn('Docker failed to run the solver 4 times', self.match.fight_error)
+
+    def test_inspect_image(self):
+        inspect_output = ('{"Id": "sha256:abc", "RepoDigests": ["generator-0@sha256:def"], '
+                          '"Config": {"Labels": {"algobattle.context_hash": "0123456789abcdef"}}}')
+        results = [mock.Mock(returncode=0, stdout=inspect_output.encode()),
+                   mock.Mock(returncode=0, stdout=b'sha256:base\n')]
+        with mock.patch('algobattle.match.subprocess.run', side_effect=lambda *args, **kwargs: results.pop(0)):
+            image_info = self.match._inspect_image('generator-0', self.tests_path + '/generator')
+        self.assertEqual(image_info, {'id': 'sha256:abc', 'repo_digests': ['generator-0@sha256:def'],
+                                      'base_image': 'python:3', 'base_image_id': 'sha256:base',
+                                      'build_fingerprint': '0123456789ab'})
+
+        # A pulled image without a build context label whose base image is not available locally
+        results = [mock.Mock(returncode=0, stdout=b'{"Id": "sha256:abc", "RepoDigests": ["foo@sha256:def"]}'),
+                   mock.Mock(returncode=1, stdout=b'')]
+        with mock.patch('algobattle.match.subprocess.run', side_effect=lambda *args, **kwargs: results.pop(0)):
+            image_info = self.match._inspect_image('generator-0', self.tests_path + '/generator')
+        self.assertEqual(image_info['repo_digests'], ['foo@sha256:def'])
+        self.assertIsNone(image_info['base_image_id'])
+        self.assertIsNone(image_info['build_fingerprint'])
+
+    def test_run_solver_failure_labels(self):
+        def run_container(role, image, input, timeout, options=None):
+            self.match.fight_logs[role] = {'stdout': b's 1 1 1', 'stderr': b'', 'exit_code': 0}
+            return b's 1 1 1'
+
+        self.match.solving_team = '0'
+        with mock.patch.object(self.match, '_run_container', side_effect=run_container):
+            with mock.patch.object(self.match, '_verify', return_value=False):
+                self.assertEqual(self.match._run_solver(1, ['i 1'], ['s 1 1 1']), 0.0)
+                self.assertEqual(self.match.fight_failure, 'wrong answer')
+
+            # A verifier that crashes or exceeds its limits is not the fault of the solver
+            with mock.patch.object(self.match, '_verify', return_value=None):
+                self.assertEqual(self.match._run_solver(1, ['i 1'], ['s 1 1 1']), 0.0)
+                self.assertEqual(self.match.fight_failure, 'verification error')
+
+    def test_insufficient_solution_quality_recorded(self):
+        self.match.build_successful = True
+        self.match.generating_team = '0'
+        self.match.solving_team = '0'
+        self.match.battle_type = 'iterated'
+        fight_records = []
+        self.match.fight_listeners.append(fight_records.append)
+
+        docker_info = mock.Mock(returncode=0, communicate=mock.Mock(return_value=(b'', b'')))
+        with mock.patch('algobattle.match.subprocess.Popen', return_value=docker_info), \
+                mock.patch.object(self.match, '_run_generator', return_value=(['i 1'], ['s 1 1 1'])), \
+                mock.patch.object(self.match, '_run_solver', return_value=2.0):
+            self.assertEqual(self.match._one_fight(1), 2.0)
+        self.assertEqual(fight_records[0]['failure'], 'wrong answer')
+        self.assertEqual(fight_records[0]['failed_role'], 'solver')
+
+    def test_failed_role_recorded(self):
+        self.match.build_successful = True
+        self.match.generating_team = '0'
+        self.match.solving_team = '0'
+        self.match.battle_type = 'iterated'
+        fight_records = []
+        self.match.fight_listeners.append(fight_records.append)
+
+        docker_info = mock.Mock(returncode=0, communicate=mock.Mock(return_value=(b'', b'')))
+        with mock.patch('algobattle.match.subprocess.Popen', return_value=docker_info):
+            with mock.patch.object(self.match, '_run_generator', return_value=None):
+                self.assertEqual(self.match._one_fight(1), 1.0)
+            with mock.patch.object(self.match, '_run_generator', return_value=(['i 1'], ['s 1 1 1'])), \
+                    mock.patch.object(self.match, '_run_solver', return_value=1.0):
+                self.assertEqual(self.match._one_fight(1), 1.0)
+        self.assertEqual([record['failed_role'] for record in fight_records], ['generator', None])
+
     def test_averaged_battle_wrapper(self):
         pass
 
+    def test_resume_averaged_battle(self):
+        fight_records = [{'battle_type': 'averaged', 'instance_size': 10, 'generating_team': '0',
+                          'solving_team': '0', 'score': score, 'seed': None, 'error': None, 'failure': None}
+                         for score in [1.0, 2.0, 1.5]]
+        for jobs in [1, 2]:
+            match = Match(self.problem, self.config, [self.team], seed=42, jobs=jobs, resumed_fights=fight_records)
+            # No fight is run, such that the outcome does not depend on the programs of the teams
+            match.build_successful = True
+            match.battle_type = 'averaged'
+            match.generating_team = '0'
+            match.solving_team = '0'
+            match.approximation_instance_size = 10
+            match.aproximation_iterations = 3
+            replayed_records = []
+            match.fight_listeners.append(replayed_records.append)
+
+            self.assertEqual(match.averaged_battle(), [1.0, 2.0, 1.5])
+            self.assertEqual(replayed_records, fight_records)
+            self.assertEqual(match.fight_index, 3)
+            seed_generator = random.Random(42)
+            for _ in range(3):
+                seed_generator.randrange(2**32)
+            self.assertEqual(match.seed_generator.randrange(2**32), seed_generator.randrange(2**32))
+
+    def test_resume_mismatching_fight(self):
+        fight_records = [{'battle_type': 'averaged', 'instance_size': 10, 'generating_team': '0',
+                          'solving_team': '0', 'score': 1.0}]
+        match = Match(self.problem, self.config, [self.team], resumed_fights=fight_records)
+        match.battle_type = 'averaged'
+        match.generating_team = '0'
+        match.solving_team = '0'
+        with self.assertRaises(ValueError):
+            match._replay_fight(instance_size=5)
+
     def test_iterated_battle_wrapper(self):
-        pass
+        instance_sizes = []
+
+        def fight(instance_size):
+            # A solver that solves exactly the instances up to size 37
+            instance_sizes.append(instance_size)
+            return 1.0 if instance_size <= 37 else 0.0
+
+        # The instance sizes do not depend on the programs of the teams
+        self.match.build_successful = True
+        self.match.generating_team = '0'
+        self.match.solving_team = '0'
+        self.match._one_fight = fight
+        self.assertEqual(self.match.iterated_battle(), 37)
+
+        instance_sizes.clear()
+        self.assertEqual(self.match.iterated_battle(BinarySearchStepStrategy(1, self.match.iteration_cap)), 37)
+        self.assertEqual(len(instance_sizes), 12)
 
     def test_one_fight(self):
         config_short_timeout = os.path.join(self.config_directory, 'config_short_run_timeout.ini')
@@ -99,18 +462,28 @@ class Matchtests(unittest.TestCase):
         match_solver_timeout.generating_team = '0'
         match_solver_timeout.solving_team = '0'
         self.assertEqual(match_solver_timeout._one_fight(1), 0.0)
+        self.assertEqual(match_solver_timeout.fight_failure, 'timeout')
 
         team = Team('0', self.tests_path + '/generator', self.tests_path + '/solver_execution_error')
         match_broken_solver = Match(self.problem, self.config, [team], cache_docker_containers=False)
         match_broken_solver.generating_team = '0'
         match_broken_solver.solving_team = '0'
         self.assertEqual(match_broken_solver._one_fight(1), 0.0)
+        self.assertEqual(match_broken_solver.fight_failure, 'crash')
+
+        team = Team('0', self.tests_path + '/generator', self.tests_path + '/solver_incomplete_output')
+        match_incomplete_output = Match(self.problem, self.config, [team], cache_docker_containers=False)
+        match_incomplete_output.generating_team = '0'
+        match_incomplete_output.solving_team = '0'
+        self.assertEqual(match_incomplete_output._one_fight(1), 0.0)
+        self.assertIn('Incomplete output', match_incomplete_output.fight_error)
 
         team = Team('0', self.tests_path + '/generator', self.tests_path + '/solver_malformed_solution')
         match_malformed_solution = Match(self.problem, self.config, [team], cache_docker_containers=False)
         match_malformed_solution.generating_team = '0'
         match_malformed_solution.solving_team = '0'
         self.assertEqual(match_malformed_solution._one_fight(1), 0.0)
+        self.assertEqual(match_malformed_solution.fight_failure, 'wrong answer')
 
         team = Team('0', self.tests_path + '/generator', self.tests_path + '/solver_wrong_certificate')
         match_wrong_certificate = Match(self.problem, self.config, [team], cache_docker_containers=False)
@@ -0,0 +1,66 @@
+""" Tests for the loading of problems.
+"""
+import unittest
+import logging
+import importlib
+import os
+import tempfile
+
+from algobattle.problem import Problem, ProblemLoadError
+
+logging.disable(logging.CRITICAL)
+
+
+class Problemtests(unittest.TestCase):
+    def setUp(self) -> None:
+        Problem = importlib.import_module('algobattle.problems.testsproblem')
+        self.tests_path = Problem.__file__[:-12]  # remove /__init__.py
+
+    def write_module(self, directory, content):
+        with open(os.path.join(directory, '__init__.py'), 'w') as module:
+            module.write(content)
+
+    def test_from_file(self):
+        self.assertEqual(Problem.from_file(self.tests_path).name, 'Tests')
+        self.assertEqual(Problem.from_file(self.tests_path + '/__init__.py').name, 'Tests')
+
+        with self.assertRaises(ProblemLoadError):
+            Problem.from_file('foo')
+
+    def test_instance_size(self):
+        with self.assertRaises(NotImplementedError):
+            Problem.from_file(self.tests_path).instance_size(['i 1'])
+
+    def test_from_file_invalid_module(self):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            self.write_module(tmpdir, 'import foo_does_not_exist')
+            with self.assertRaisesRegex(ProblemLoadError, 'foo_does_not_exist'):
+                Problem.from_file(tmpdir)
+
+            self.write_module(tmpdir, 'class Foo:\n    pass')
+            with self.assertRaisesRegex(ProblemLoadError, 'does not export a class named Problem'):
+                Problem.from_file(tmpdir)
+
+            self.write_module(tmpdir, 'class Problem:\n    pass')
+            with self.assertRaisesRegex(ProblemLoadError, 'not a subclass'):
+                Problem.from_file(tmpdir)
+
+    def test_from_file_invalid_problem(self):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            self.write_module(tmpdir, 'from algobattle.problem import Problem as P\n'
+                                      'class Problem(P):\n'
+                                      '    name = "Foo"\n'
+                                      '    parser = None\n'
+                                      '    verifier = None\n')
+            with self.assertRaises(ProblemLoadError) as context:
+                Problem.from_file(tmpdir)
+            message = str(context.exception)
+            self.assertIn('the attribute approximable is not defined', message)
+            self.assertIn('the attribute n_start is not defined', message)
+            self.assertIn('the parser is not an object', message)
+            self.assertIn('the verifier is not an object', message)
+            self.assertNotIn('the attribute name', message)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -2,8 +2,11 @@
 """
 import unittest
 import logging
+import importlib.util
+import os
+import random
 
-from algobattle.problems.pairsum import parser, verifier
+from algobattle.problems.pairsum import parser, verifier, problem
 
 logging.disable(logging.CRITICAL)
 
@@ -93,5 +96,48 @@ class Verifiertests(unittest.TestCase):
         self.assertEqual(self.verifier.calculate_approximation_ratio(instance, 10, solution, solution), 1.0)
 
 
+class Generatortests(unittest.TestCase):
+    def setUp(self) -> None:
+        generator_path = os.path.join(os.path.dirname(parser.__file__), 'generator_random', 'main.py')
+        spec = importlib.util.spec_from_file_location('generator_random', generator_path)
+        self.generator = importlib.util.module_from_spec(spec)
+        spec.loader.exec_module(self.generator)
+        self.parser = parser.PairsumParser()
+        self.verifier = verifier.PairsumVerifier()
+        random.seed(0)
+
+    def check_instance(self, instance, solution, instance_size):
+        encoded_output = self.parser.encode(instance) + b'\n' + self.parser.encode(solution)
+        raw_instance, raw_solution = self.parser.split_into_instance_and_solution(self.parser.decode(encoded_output))
+        parsed_instance = self.parser.parse_instance(raw_instance, instance_size)
+        parsed_solution = self.parser.parse_solution(raw_solution, instance_size)
+        self.assertEqual(parsed_instance, instance)
+        self.assertEqual(problem.Pairsum().instance_size(parsed_instance), instance_size)
+        self.assertTrue(self.verifier.verify_semantics_of_solution(parsed_solution, instance_size, True))
+        self.assertTrue(self.verifier.verify_solution_against_instance(parsed_instance, parsed_solution,
+                                                                       instance_size, True))
+
+    def test_random_instance(self):
+        instance, solution = self.generator.random_instance(10)
+        self.check_instance(instance, solution, 10)
+
+    def test_adversarial_instance(self):
+        for instance_size in [4, 5, 50]:
+            instance, solution = self.generator.adversarial_instance(instance_size)
+            self.check_instance(instance, solution, instance_size)
+
+            # Only the pairs of the planted solution have the same sum
+            pair_sums = dict()
+            for i in range(instance_size):
+                for j in range(i + 1, instance_size):
+                    pair_sums.setdefault(instance[i] + instance[j], []).append((i, j))
+            self.assertEqual([pairs for pairs in pair_sums.values() if len(pairs) > 1],
+                             [sorted([tuple(sorted(solution[:2])), tuple(sorted(solution[2:]))])])
+
+    def test_adversarial_instance_max_size(self):
+        instance, solution = self.generator.adversarial_instance(10**5)
+        self.check_instance(instance, solution, 10**5)
+
+
 if __name__ == '__main__':
     unittest.main()
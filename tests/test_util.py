@@ -3,15 +3,27 @@
 import unittest
 import logging
 import importlib
+import importlib.util
 import os
+import time
+import tempfile
+import shutil
+import gzip
+import mmap
+import configparser
 
 import algobattle
 from algobattle.match import Match
 from algobattle.team import Team
-from algobattle.util import import_problem_from_path, measure_runtime_overhead, calculate_points, run_subprocess
+from algobattle.util import import_problem_from_path, measure_runtime_overhead, calculate_points, run_subprocess, \
+    run_with_limits, hash_build_context, truncate_output, read_cgroup_cpu_time, decompress_output, \
+    is_docker_daemon_error, load_config, ConfigError, read_base_image, format_phase_times, read_run_parameter
 
 logging.disable(logging.CRITICAL)
 
+TOML_AVAILABLE = importlib.util.find_spec('tomllib') is not None or importlib.util.find_spec('toml') is not None
+YAML_AVAILABLE = importlib.util.find_spec('yaml') is not None
+
 
 class Matchtests(unittest.TestCase):
     def setUp(self) -> None:
@@ -51,6 +63,177 @@ class Matchtests(unittest.TestCase):
         raw_output, _ = run_subprocess(match_run_timeout.base_build_command + ['generator-0'], 0, 2)
         self.assertIsNone(raw_output)
 
+    def test_run_with_limits(self):
+        self.assertEqual(run_with_limits(max, (1, 2), 10, 2000), (2, None))
+
+        result, error = run_with_limits(time.sleep, (10,), 1, 2000)
+        self.assertIsNone(result)
+        self.assertIn('time limit', error)
+
+        # A modest allocation passes, regardless of the address space that is already reserved by this process
+        with mmap.mmap(-1, 3 * 1024 ** 3):
+            result, error = run_with_limits(lambda: len(bytearray(50 * 1024 ** 2)), (), 10, 2000)
+        self.assertEqual((result, error), (50 * 1024 ** 2, None))
+
+        result, error = run_with_limits(bytearray, (4 * 1024 ** 3,), 10, 2000)
+        self.assertIsNone(result)
+        self.assertIn('memory limit', error)
+
+        result, error = run_with_limits(int, ('foo',), 10, 2000)
+        self.assertIsNone(result)
+        self.assertIn('exception', error)
+
+        result, error = run_with_limits(os._exit, (3,), 10, 2000)
+        self.assertIsNone(result)
+        self.assertIn('exit code 3', error)
+
+    def test_hash_build_context(self):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            context0 = os.path.join(tmpdir, 'context0')
+            context1 = os.path.join(tmpdir, 'context1')
+            shutil.copytree(self.tests_path + '/generator', context0)
+            shutil.copytree(self.tests_path + '/generator', context1)
+            self.assertEqual(hash_build_context(context0), hash_build_context(context1))
+
+            with open(os.path.join(context1, 'Dockerfile'), 'a') as dockerfile:
+                dockerfile.write('\nRUN true')
+            self.assertNotEqual(hash_build_context(context0), hash_build_context(context1))
+
+            shutil.copytree(self.tests_path + '/generator', context1, dirs_exist_ok=True)
+            self.assertEqual(hash_build_context(context0), hash_build_context(context1))
+
+            os.rename(os.path.join(context1, 'main.py'), os.path.join(context1, 'other.py'))
+            self.assertNotEqual(hash_build_context(context0), hash_build_context(context1))
+
+    def test_read_base_image(self):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            self.assertIsNone(read_base_image(tmpdir))
+
+            with open(os.path.join(tmpdir, 'Dockerfile'), 'w') as dockerfile:
+                dockerfile.write('FROM --platform=linux/amd64 rust:1.70 AS build\nRUN cargo build\n'
+                                 'FROM debian:bookworm-slim\nCOPY --from=build /app /app\n')
+            self.assertEqual(read_base_image(tmpdir), 'debian:bookworm-slim')
+
+            # Stages built upon earlier stages refer to the base image of the earlier stage
+            with open(os.path.join(tmpdir, 'Dockerfile'), 'w') as dockerfile:
+                dockerfile.write('from python:3.9 as base\nFROM base\nCMD python main.py\n')
+            self.assertEqual(read_base_image(tmpdir), 'python:3.9')
+
+    def test_read_cgroup_cpu_time(self):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            self.assertIsNone(read_cgroup_cpu_time('foo', tmpdir))
+
+            os.makedirs(os.path.join(tmpdir, 'system.slice', 'docker-foo.scope'))
+            with open(os.path.join(tmpdir, 'system.slice', 'docker-foo.scope', 'cpu.stat'), 'w') as cpu_stat:
+                cpu_stat.write('usage_usec 1500000\nuser_usec 1000000\nsystem_usec 500000\n')
+            self.assertEqual(read_cgroup_cpu_time('foo', tmpdir), 1.5)
+
+            os.makedirs(os.path.join(tmpdir, 'cpuacct', 'docker', 'bar'))
+            with open(os.path.join(tmpdir, 'cpuacct', 'docker', 'bar', 'cpuacct.usage'), 'w') as cpuacct_usage:
+                cpuacct_usage.write('250000000\n')
+            self.assertEqual(read_cgroup_cpu_time('bar', tmpdir), 0.25)
+
+    def test_decompress_output(self):
+        self.assertEqual(decompress_output(b'i 1\ns 1 1 1'), b'i 1\ns 1 1 1')
+        self.assertEqual(decompress_output(gzip.compress(b'i 1\ns 1 1 1')), b'i 1\ns 1 1 1')
+
+        with self.assertRaises(EOFError):
+            decompress_output(gzip.compress(b'i 1\ns 1 1 1')[:-10])
+
+    def test_is_docker_daemon_error(self):
+        self.assertTrue(is_docker_daemon_error({'exit_code': 125, 'stderr': b'docker: Error response from daemon: '
+                                                                           b'error creating overlay mount.\n'}))
+        # A program that exits with the same code on its own
+        self.assertFalse(is_docker_daemon_error({'exit_code': 125, 'stderr': b'Segmentation fault\n'}))
+        self.assertFalse(is_docker_daemon_error({'exit_code': 1, 'stderr': b'docker: foo'}))
+        # Timeouts
+        self.assertFalse(is_docker_daemon_error({'exit_code': None, 'stderr': None}))
+
+    def write_and_load_configs(self, contents):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            for name, content in contents.items():
+                path = os.path.join(tmpdir, name)
+                with open(path, 'w') as config_file:
+                    config_file.write(content)
+                config = load_config(path)
+                self.assertEqual(config['run_parameters'].getint('timeout_build'), 600)
+                self.assertTrue(config['run_parameters'].getboolean('cache'))
+
+    def assert_invalid_configs(self, contents):
+        with tempfile.TemporaryDirectory() as tmpdir:
+            for name, (content, message) in contents.items():
+                path = os.path.join(tmpdir, name)
+                with open(path, 'w') as config_file:
+                    config_file.write(content)
+                with self.assertRaises(ConfigError) as context:
+                    load_config(path)
+                self.assertIn(message, str(context.exception))
+
+    def test_load_config(self):
+        self.write_and_load_configs({'config.ini': '[run_parameters]\ntimeout_build = 600\ncache = true\n'})
+
+        # Missing files are ignored
+        with tempfile.TemporaryDirectory() as tmpdir:
+            self.assertEqual(load_config(os.path.join(tmpdir, 'missing.yml')).sections(), [])
+
+    @unittest.skipUnless(TOML_AVAILABLE, 'Reading TOML config files requires Python 3.11 or the toml package.')
+    def test_load_config_toml(self):
+        self.write_and_load_configs({'config.toml': '[run_parameters]\ntimeout_build = 600\ncache = true\n'})
+
+    @unittest.skipUnless(YAML_AVAILABLE, 'Reading YAML config files requires the PyYAML package.')
+    def test_load_config_yaml(self):
+        self.write_and_load_configs({'config.yaml': 'run_parameters:\n  timeout_build: 600\n  cache: true\n'})
+
+    def test_load_config_invalid(self):
+        self.assert_invalid_configs({'malformed.ini': ('timeout_build = 600\n', 'could not be parsed')})
+
+    def test_read_run_parameter(self):
+        config = configparser.ConfigParser()
+        self.assertEqual(read_run_parameter(config, 'cpus', fallback=1), 1)
+        with self.assertRaises(ConfigError) as context:
+            read_run_parameter(config, 'timeout_build')
+        self.assertIn('"run_parameters.timeout_build"', str(context.exception))
+
+        config.read_dict({'run_parameters': {'timeout_build': '600', 'cpus_solver': '1.5', 'timeout_solver': 'foo'}})
+        self.assertEqual(read_run_parameter(config, 'timeout_build'), 600)
+        self.assertEqual(read_run_parameter(config, 'cpus_solver', float), 1.5)
+        with self.assertRaises(ConfigError) as context:
+            read_run_parameter(config, 'timeout_solver')
+        self.assertEqual(str(context.exception),
+                         'The option "run_parameters.timeout_solver" of the config file is not an integer.')
+        with self.assertRaises(ConfigError) as context:
+            read_run_parameter(config, 'cpus_solver')
+        self.assertIn('"run_parameters.cpus_solver"', str(context.exception))
+
+    @unittest.skipUnless(TOML_AVAILABLE, 'Reading TOML config files requires Python 3.11 or the toml package.')
+    def test_load_config_invalid_toml(self):
+        self.assert_invalid_configs({
+            'no_table.toml': ('timeout_build = 600\n', '"timeout_build"'),
+            'malformed.toml': ('[run_parameters\n', 'could not be parsed')
+        })
+
+    @unittest.skipUnless(YAML_AVAILABLE, 'Reading YAML config files requires the PyYAML package.')
+    def test_load_config_invalid_yaml(self):
+        self.assert_invalid_configs({
+            'nested.yaml': ('run_parameters:\n  timeout_build:\n    - 600\n', '"run_parameters.timeout_build"')
+        })
+
+    def test_format_phase_times(self):
+        self.assertEqual(format_phase_times({'build': 1.0, 'solve': 3.0, 'check_semantics': 0.0}),
+                         ['solve                 3.00s    75.0%',
+                          'build                 1.00s    25.0%',
+                          'check_semantics       0.00s     0.0%',
+                          'total                 4.00s'])
+        self.assertEqual(format_phase_times({'build': 0.0}), ['build                 0.00s     0.0%',
+                                                               'total                 0.00s'])
+
+    def test_truncate_output(self):
+        self.assertIsNone(truncate_output(None, 10))
+        self.assertEqual(truncate_output(b'foo', 10), 'foo')
+        self.assertEqual(truncate_output(b'foobar', 3), 'foo... (3 more bytes truncated)')
+        self.assertEqual(truncate_output(b'foobar', 0), 'foobar')
+        self.assertEqual(truncate_output(b'\xff\xfe', 10), '\\xff\\xfe')
+
 
 if __name__ == '__main__':
     unittest.main()
@@ -0,0 +1,57 @@
+""" Tests for the verifier base classes.
+"""
+import unittest
+import logging
+
+from algobattle.verifier import DecisionVerifier, DecisionSolution
+
+logging.disable(logging.CRITICAL)
+
+
+class EvenNumberVerifier(DecisionVerifier):
+    """Decides whether a list of numbers contains an even number, certified by the index of one."""
+
+    def verify_certificate(self, instance, answer, certificate, instance_size):
+        return answer and 0 <= certificate < len(instance) and instance[certificate] % 2 == 0
+
+
+class DecisionVerifiertests(unittest.TestCase):
+    def setUp(self) -> None:
+        self.verifier = EvenNumberVerifier()
+        self.instance = [1, 3, 4]
+
+    def test_verify_semantics_of_solution(self):
+        self.assertTrue(self.verifier.verify_semantics_of_solution(DecisionSolution(True, 2), 10, False))
+        self.assertFalse(self.verifier.verify_semantics_of_solution([2], 10, False))
+
+    def test_verify_solution_against_instance(self):
+        self.assertTrue(self.verifier.verify_solution_against_instance(self.instance, DecisionSolution(True, 2),
+                                                                       10, False))
+        self.assertFalse(self.verifier.verify_solution_against_instance(self.instance, DecisionSolution(True, 0),
+                                                                        10, False))
+        # Positive answers need a certificate, negative ones do not
+        self.assertFalse(self.verifier.verify_solution_against_instance(self.instance, DecisionSolution(True),
+                                                                        10, False))
+        self.assertTrue(self.verifier.verify_solution_against_instance(self.instance, DecisionSolution(False),
+                                                                       10, False))
+
+    def test_calculate_approximation_ratio(self):
+        generator_solution = DecisionSolution(True, 2)
+        self.assertEqual(self.verifier.calculate_approximation_ratio(self.instance, 10, generator_solution,
+                                                                     DecisionSolution(True, 2)), 1.0)
+        self.assertEqual(self.verifier.calculate_approximation_ratio(self.instance, 10, generator_solution,
+                                                                     DecisionSolution(False)), 0.0)
+
+    def test_calculate_approximation_ratio_disproved_generator(self):
+        # The generator claims without proof that there is no even number
+        generator_solution = DecisionSolution(False)
+        self.assertEqual(self.verifier.calculate_approximation_ratio(self.instance, 10, generator_solution,
+                                                                     DecisionSolution(True, 2)), 1.0)
+        self.assertEqual(self.verifier.calculate_approximation_ratio(self.instance, 10, generator_solution,
+                                                                     DecisionSolution(True, 0)), 0.0)
+        self.assertEqual(self.verifier.calculate_approximation_ratio(self.instance, 10, generator_solution,
+                                                                     DecisionSolution(True)), 0.0)
+
+
+if __name__ == '__main__':
+    unittest.main()